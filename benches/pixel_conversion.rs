@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use x11_dl::xlib::{self, Xlib};
+
+use borkscreen::window_system::pixel_format::{ByteOrder, PixelBuffer, PixelFormat};
+
+// A 4K window, as captured from a 24 bits deep TrueColor visual
+const WIDTH: u32 = 3840;
+const HEIGHT: u32 = 2160;
+const RED_MASK: u64 = 0xff0000;
+const GREEN_MASK: u64 = 0x00ff00;
+const BLUE_MASK: u64 = 0x0000ff;
+
+fn pixel_conversion(c: &mut Criterion) {
+    let format = PixelFormat::from_masks(RED_MASK, GREEN_MASK, BLUE_MASK, 24);
+    let mut data: Vec<u8> = (0..WIDTH * HEIGHT * 4).map(|i| (i % 251) as u8).collect();
+    let xlib = Xlib::open().expect("Unable to load the Xlib library");
+    // The same pixels, as XGetImage would have returned them, without needing an X server
+    let mut image = xlib::XImage {
+        width: WIDTH as _,
+        height: HEIGHT as _,
+        xoffset: 0,
+        format: xlib::ZPixmap,
+        data: data.as_mut_ptr() as _,
+        byte_order: xlib::LSBFirst,
+        bitmap_unit: 32,
+        bitmap_bit_order: xlib::LSBFirst,
+        bitmap_pad: 32,
+        depth: 24,
+        bytes_per_line: (WIDTH * 4) as _,
+        bits_per_pixel: 32,
+        red_mask: RED_MASK,
+        green_mask: GREEN_MASK,
+        blue_mask: BLUE_MASK,
+        obdata: std::ptr::null_mut(),
+        funcs: unsafe { std::mem::zeroed() },
+    };
+    if unsafe { (xlib.XInitImage)(&mut image) } == 0 {
+        panic!("Unable to initialize the XImage");
+    }
+    let image: *mut xlib::XImage = &mut image;
+    let buffer = PixelBuffer {
+        data: &data,
+        width: WIDTH,
+        height: HEIGHT,
+        bytes_per_line: WIDTH as usize * 4,
+        bits_per_pixel: 32,
+        byte_order: ByteOrder::LsbFirst,
+    };
+
+    let mut group = c.benchmark_group("4K window");
+    group.sample_size(10);
+    // The conversion the bulk one replaces, through one XGetPixel call per pixel
+    group.bench_function("XGetPixel", |b| b.iter(|| {
+        image::RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| unsafe {
+            format.to_rgba((xlib.XGetPixel)(black_box(image), x as i32, y as i32))
+        })
+    }));
+    group.bench_function("bulk", |b| b.iter(|| format.convert(black_box(&buffer))));
+    group.finish();
+}
+
+criterion_group!(benches, pixel_conversion);
+criterion_main!(benches);
@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+/// A frame of an animation, displayed for `delay` before the next one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationFrame {
+    pub image: image::RgbaImage,
+    pub delay: Duration,
+}
+
+/// Accumulates captures along with the time they were taken at, and turns them into animation frames.
+/// A capture identical to the previous one only extends the display time of the latter.
+#[derive(Default)]
+pub struct AnimationRecorder {
+    captures: Vec<(Instant, image::RgbaImage)>,
+}
+
+impl AnimationRecorder {
+    pub fn new() -> Self {
+        Self { captures: Vec::new() }
+    }
+
+    pub fn push(&mut self, captured_at: Instant, image: image::RgbaImage) {
+        if self.captures.last().is_some_and(|(_, previous)| *previous == image) {
+            return;
+        }
+        self.captures.push((captured_at, image));
+    }
+
+    /// Each frame lasts until the next distinct capture, and the last one until `end`.
+    /// Captures whose size differs from the first one (e.g. the window was resized) are cropped or padded.
+    pub fn into_frames(self, end: Instant) -> Vec<AnimationFrame> {
+        let Some((_, first)) = self.captures.first() else {
+            return Vec::new();
+        };
+        let (width, height) = first.dimensions();
+        let ends: Vec<Instant> = self.captures.iter().skip(1).map(|(captured_at, _)| *captured_at)
+            .chain(std::iter::once(end))
+            .collect();
+        self.captures.into_iter()
+            .zip(ends)
+            .map(|((captured_at, image), frame_end)| AnimationFrame {
+                image: resize_canvas(image, width, height),
+                delay: frame_end.saturating_duration_since(captured_at),
+            })
+            .collect()
+    }
+}
+
+fn resize_canvas(image: image::RgbaImage, width: u32, height: u32) -> image::RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image;
+    }
+    let mut canvas = image::RgbaImage::from_pixel(width, height, image::Rgba([0, 0, 0, 255]));
+    image::imageops::replace(&mut canvas, &image, 0, 0);
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::animation::AnimationRecorder;
+
+    const WHITE: image::Rgba<u8> = image::Rgba([255, 255, 255, 255]);
+    const BLACK: image::Rgba<u8> = image::Rgba([0, 0, 0, 255]);
+
+    #[test]
+    fn it_should_derive_the_delays_from_the_capture_times() {
+        // Given
+        let start = Instant::now();
+        let mut recorder = AnimationRecorder::new();
+        recorder.push(start, image::RgbaImage::from_pixel(1, 1, WHITE));
+        recorder.push(start + Duration::from_millis(120), image::RgbaImage::from_pixel(1, 1, BLACK));
+
+        // When
+        let frames = recorder.into_frames(start + Duration::from_millis(200));
+
+        // Then
+        let delays: Vec<_> = frames.iter().map(|frame| frame.delay).collect();
+        assert_eq!(delays, vec![Duration::from_millis(120), Duration::from_millis(80)]);
+    }
+
+    #[test]
+    fn it_should_merge_identical_consecutive_captures() {
+        // Given
+        let start = Instant::now();
+        let mut recorder = AnimationRecorder::new();
+        recorder.push(start, image::RgbaImage::from_pixel(1, 1, WHITE));
+        recorder.push(start + Duration::from_millis(100), image::RgbaImage::from_pixel(1, 1, WHITE));
+        recorder.push(start + Duration::from_millis(200), image::RgbaImage::from_pixel(1, 1, BLACK));
+        recorder.push(start + Duration::from_millis(300), image::RgbaImage::from_pixel(1, 1, WHITE));
+
+        // When
+        let frames = recorder.into_frames(start + Duration::from_millis(400));
+
+        // Then
+        let colors: Vec<_> = frames.iter().map(|frame| *frame.image.get_pixel(0, 0)).collect();
+        assert_eq!(colors, vec![WHITE, BLACK, WHITE]);
+        assert_eq!(frames[0].delay, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn it_should_fit_the_captures_to_the_size_of_the_first_one() {
+        // Given
+        let start = Instant::now();
+        let mut recorder = AnimationRecorder::new();
+        recorder.push(start, image::RgbaImage::from_pixel(2, 2, WHITE));
+        recorder.push(start + Duration::from_millis(100), image::RgbaImage::from_pixel(1, 3, WHITE));
+
+        // When
+        let frames = recorder.into_frames(start + Duration::from_millis(200));
+
+        // Then
+        assert_eq!(frames[1].image.dimensions(), (2, 2));
+        assert_eq!(frames[1].image.get_pixel(0, 1), &WHITE);
+        assert_eq!(frames[1].image.get_pixel(1, 1), &BLACK);
+    }
+}
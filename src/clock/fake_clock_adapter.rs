@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use chrono::TimeZone;
+
+use crate::gateways::ClockGateway;
+
+/// A clock whose time only advances when sleeping, without actually waiting.
+/// Its wall-clock time starts at 2024-05-01 12:34:56 (local time).
+pub struct FakeClockAdapter {
+    start: Instant,
+    now: Rc<RefCell<Instant>>,
+    /// Shared, so the sleeps can still be inspected once the adapter is given to a use case.
+    pub sleeps: Rc<RefCell<Vec<Duration>>>,
+}
+
+impl FakeClockAdapter {
+    pub fn new() -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            now: Rc::new(RefCell::new(start)),
+            sleeps: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+}
+
+impl Default for FakeClockAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockGateway for FakeClockAdapter {
+    fn now(&self) -> Instant {
+        *self.now.borrow()
+    }
+
+    fn date_time(&self) -> chrono::DateTime<chrono::Local> {
+        let start = chrono::Local.with_ymd_and_hms(2024, 5, 1, 12, 34, 56).unwrap();
+        start + self.now().duration_since(self.start)
+    }
+
+    fn sleep(&self, duration: Duration) {
+        *self.now.borrow_mut() += duration;
+        self.sleeps.borrow_mut().push(duration);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod system_clock_adapter;
+pub mod fake_clock_adapter;
\ No newline at end of file
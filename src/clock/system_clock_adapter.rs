@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+
+use crate::gateways::ClockGateway;
+
+pub struct SystemClockAdapter;
+
+impl SystemClockAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SystemClockAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockGateway for SystemClockAdapter {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn date_time(&self) -> chrono::DateTime<chrono::Local> {
+        chrono::Local::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
@@ -0,0 +1,106 @@
+/// The image of the mouse cursor, with the position of its top left corner relatively to a window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cursor {
+    pub x: i32,
+    pub y: i32,
+    pub image: image::RgbaImage,
+}
+
+impl Cursor {
+    /// Alpha-blends the cursor onto a capture of the window whose top left corner is at `(origin_x, origin_y)`
+    /// within the window. The parts of the cursor outside of the capture are left out.
+    pub fn draw_onto(&self, capture: &mut image::RgbaImage, origin_x: u32, origin_y: u32) {
+        let offset_x = self.x as i64 - origin_x as i64;
+        let offset_y = self.y as i64 - origin_y as i64;
+        for (x, y, pixel) in self.image.enumerate_pixels() {
+            let target_x = offset_x + x as i64;
+            let target_y = offset_y + y as i64;
+            if target_x < 0 || target_y < 0 || target_x >= capture.width() as i64 || target_y >= capture.height() as i64 {
+                continue;
+            }
+            let target = capture.get_pixel_mut(target_x as u32, target_y as u32);
+            *target = blend(*pixel, *target);
+        }
+    }
+}
+
+// Source over compositing of straight (not premultiplied) alpha colors
+fn blend(source: image::Rgba<u8>, destination: image::Rgba<u8>) -> image::Rgba<u8> {
+    let source_alpha = source[3] as u32;
+    let destination_alpha = destination[3] as u32 * (255 - source_alpha) / 255;
+    let alpha = source_alpha + destination_alpha;
+    if alpha == 0 {
+        return image::Rgba([0, 0, 0, 0]);
+    }
+    let channel = |i: usize| {
+        ((source[i] as u32 * source_alpha + destination[i] as u32 * destination_alpha + alpha / 2) / alpha) as u8
+    };
+    image::Rgba([channel(0), channel(1), channel(2), alpha as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cursor::Cursor;
+
+    const WHITE: image::Rgba<u8> = image::Rgba([255, 255, 255, 255]);
+    const BLACK: image::Rgba<u8> = image::Rgba([0, 0, 0, 255]);
+
+    #[test]
+    fn it_should_draw_the_cursor_at_its_position() {
+        // Given
+        let cursor = Cursor { x: 2, y: 1, image: image::RgbaImage::from_pixel(2, 2, BLACK) };
+        let mut capture = image::RgbaImage::from_pixel(5, 5, WHITE);
+
+        // When
+        cursor.draw_onto(&mut capture, 0, 0);
+
+        // Then
+        assert_eq!(capture.get_pixel(2, 1), &BLACK);
+        assert_eq!(capture.get_pixel(3, 2), &BLACK);
+        assert_eq!(capture.get_pixel(1, 1), &WHITE);
+        assert_eq!(capture.get_pixel(4, 3), &WHITE);
+    }
+
+    #[test]
+    fn it_should_draw_the_cursor_relatively_to_the_captured_region() {
+        // Given
+        let cursor = Cursor { x: 12, y: 21, image: image::RgbaImage::from_pixel(2, 2, BLACK) };
+        let mut capture = image::RgbaImage::from_pixel(5, 5, WHITE);
+
+        // When
+        cursor.draw_onto(&mut capture, 10, 20);
+
+        // Then
+        assert_eq!(capture.get_pixel(2, 1), &BLACK);
+        assert_eq!(capture.get_pixel(1, 1), &WHITE);
+    }
+
+    #[test]
+    fn it_should_blend_the_translucent_pixels_of_the_cursor() {
+        // Given
+        let cursor = Cursor { x: 0, y: 0, image: image::RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 128])) };
+        let mut capture = image::RgbaImage::from_pixel(1, 1, WHITE);
+
+        // When
+        cursor.draw_onto(&mut capture, 0, 0);
+
+        // Then
+        let image::Rgba([r, g, b, a]) = *capture.get_pixel(0, 0);
+        assert!((126..=128).contains(&r) && r == g && g == b, "pixel = {:?}", [r, g, b]);
+        assert_eq!(a, 255);
+    }
+
+    #[test]
+    fn it_should_clip_the_cursor_partially_outside_of_the_capture() {
+        // Given
+        let cursor = Cursor { x: -1, y: 4, image: image::RgbaImage::from_pixel(2, 2, BLACK) };
+        let mut capture = image::RgbaImage::from_pixel(5, 5, WHITE);
+
+        // When
+        cursor.draw_onto(&mut capture, 0, 0);
+
+        // Then
+        assert_eq!(capture.get_pixel(0, 4), &BLACK);
+        assert_eq!(capture.get_pixel(1, 4), &WHITE);
+    }
+}
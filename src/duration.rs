@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+/// Parses a duration made of a number followed by a unit (`ms`, `s` or `m`), e.g. `500ms` or `1.5s`.
+/// A number without unit is a number of seconds.
+pub fn parse_duration(value: &str) -> anyhow::Result<Duration> {
+    let invalid = || anyhow::anyhow!("Invalid duration {:?}, expected a number followed by ms, s or m (e.g. 5s)", value);
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+/// Same as `parse_duration`, for the durations that have to be longer than zero (e.g. the interval of a loop).
+pub fn parse_positive_duration(value: &str) -> anyhow::Result<Duration> {
+    let duration = parse_duration(value)?;
+    if duration.is_zero() {
+        anyhow::bail!("Invalid duration {:?}, expected a duration longer than zero", value);
+    }
+    Ok(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::duration::{parse_duration, parse_positive_duration};
+
+    #[test]
+    fn it_should_parse_durations_with_units() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn it_should_parse_numbers_as_seconds() {
+        assert_eq!(parse_duration("3").unwrap(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn it_should_reject_invalid_durations() {
+        for value in ["", "s", "5h", "five", "-1s", "1.2.3s"] {
+            let result = parse_duration(value);
+            assert!(result.is_err(), "{:?} should be invalid", value);
+            assert!(result.unwrap_err().to_string().starts_with("Invalid duration"));
+        }
+    }
+
+    #[test]
+    fn it_should_reject_zero_where_a_positive_duration_is_expected() {
+        assert_eq!(parse_positive_duration("10ms").unwrap(), Duration::from_millis(10));
+        for value in ["0", "0s", "0ms"] {
+            let result = parse_positive_duration(value);
+            assert!(result.is_err(), "{:?} should be invalid", value);
+        }
+    }
+}
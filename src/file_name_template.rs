@@ -0,0 +1,291 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+
+use crate::results::WindowInfo;
+
+// Output file names can contain the following placeholders, filled in as soon as their value is known:
+// - `{date}` or `{date:FORMAT}`: the local date and time of the capture, with a strftime format
+// - `{title}`, `{class}`: the title and WM_CLASS class name of the captured window
+// - `{window_id}` (or its shorter `{id}` alias): the hexadecimal id of the captured window
+// - `{seq}`: the zero-padded sequence number of the capture, when taking several ones
+// `{{` and `}}` stand for literal braces.
+const DATE: &str = "date";
+const TITLE: &str = "title";
+const CLASS: &str = "class";
+const WINDOW_ID: &str = "window_id";
+const ID: &str = "id";
+const SEQUENCE: &str = "seq";
+const PLACEHOLDERS: [&str; 6] = [DATE, TITLE, CLASS, WINDOW_ID, ID, SEQUENCE];
+const WINDOW_PLACEHOLDERS: [&str; 4] = [TITLE, CLASS, WINDOW_ID, ID];
+
+const DEFAULT_DATE_FORMAT: &str = "%Y%m%d-%H%M%S";
+// Keeps the file names below the usual limit of 255 bytes, even with several long titles. The limit is in bytes, as
+// non-ASCII titles take several bytes per character
+const MAX_VALUE_BYTES: usize = 64;
+
+enum Segment<'a> {
+    Literal(String),
+    Placeholder { name: &'a str, format: Option<&'a str> },
+}
+
+/// Replaces `{seq}` in the file name with the zero-padded sequence number. If there is no such placeholder, the
+/// number is appended to the file name, before its extension (e.g. `capture.png` becomes `capture-0001.png`).
+pub fn with_sequence_number(template: &str, seq: u32) -> anyhow::Result<String> {
+    let template = if contains_any(template, &[SEQUENCE]) {
+        template.to_string()
+    } else {
+        append_to_file_stem(template, "{seq}")
+    };
+    fill(&template, |name, _| Ok((name == SEQUENCE).then(|| format!("{:04}", seq))))
+}
+
+/// Appends the window id to the file name (like the sequence number) if it contains no `{window_id}` or `{id}`
+/// placeholder, so that the captures of different windows never overwrite each other.
+pub fn distinct_per_window(template: &str) -> String {
+    if contains_any(template, &[WINDOW_ID, ID]) {
+        return template.to_string();
+    }
+    append_to_file_stem(template, "{window_id}")
+}
+
+/// Whether the file name needs the window information to be filled in.
+pub fn needs_window(template: &str) -> bool {
+    contains_any(template, &WINDOW_PLACEHOLDERS)
+}
+
+/// Replaces the title, class and id of the window, the first two being made safe for a file name.
+pub fn with_window(template: &str, window: &WindowInfo) -> anyhow::Result<String> {
+    fill(template, |name, _| Ok(match name {
+        TITLE => Some(sanitize(window.title.as_deref().unwrap_or_default(), "untitled")),
+        CLASS => Some(sanitize(window.class.as_deref().unwrap_or_default(), "unknown")),
+        WINDOW_ID | ID => Some(format!("{:#x}", window.id)),
+        _ => None,
+    }))
+}
+
+pub fn with_date(template: &str, date: &DateTime<Local>) -> anyhow::Result<String> {
+    fill(template, |name, format| {
+        if name != DATE {
+            return Ok(None);
+        }
+        let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+        let items: Vec<Item> = StrftimeItems::new(format).collect();
+        if items.contains(&Item::Error) {
+            anyhow::bail!("Invalid date format {:?} in the output file name", format);
+        }
+        Ok(Some(sanitize(&date.format_with_items(items.into_iter()).to_string(), "date")))
+    })
+}
+
+/// Returns the final file name, once every placeholder has been filled in.
+pub fn render(template: &str) -> anyhow::Result<String> {
+    let mut path = String::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Literal(literal) => path.push_str(&literal),
+            Segment::Placeholder { name: SEQUENCE, .. } => {
+                anyhow::bail!("The {{seq}} placeholder can only be used when taking several screenshots");
+            }
+            Segment::Placeholder { name, .. } => {
+                anyhow::bail!("The {{{}}} placeholder is not available for this capture", name);
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Replaces the placeholders for which `value` returns some text, and keeps the other ones for a later stage.
+fn fill<F>(template: &str, value: F) -> anyhow::Result<String> where
+    F: Fn(&str, Option<&str>) -> anyhow::Result<Option<String>>,
+{
+    let mut filled = String::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Literal(literal) => filled.push_str(&escape(&literal)),
+            Segment::Placeholder { name, format } => match (value(name, format)?, format) {
+                (Some(value), _) => filled.push_str(&escape(&value)),
+                (None, Some(format)) => filled.push_str(&format!("{{{}:{}}}", name, format)),
+                (None, None) => filled.push_str(&format!("{{{}}}", name)),
+            },
+        }
+    }
+    Ok(filled)
+}
+
+fn parse(template: &str) -> anyhow::Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(position) = rest.find(['{', '}']) {
+        literal.push_str(&rest[..position]);
+        let brace = &rest[position..position + 1];
+        rest = &rest[position + 1..];
+        if let Some(after_escaped_brace) = rest.strip_prefix(brace) {
+            literal.push_str(brace);
+            rest = after_escaped_brace;
+            continue;
+        }
+        if brace == "}" {
+            anyhow::bail!("Unexpected \"}}\" in the output file name {:?}, use \"}}}}\" for a literal brace", template);
+        }
+        let Some(end) = rest.find('}') else {
+            anyhow::bail!("Unclosed placeholder in the output file name {:?}, use \"{{{{\" for a literal brace", template);
+        };
+        let (name, format) = match rest[..end].split_once(':') {
+            Some((name, format)) => (name, Some(format)),
+            None => (&rest[..end], None),
+        };
+        if !PLACEHOLDERS.contains(&name) {
+            anyhow::bail!("Unknown placeholder {{{}}} in the output file name, expected one of {}", name, PLACEHOLDERS
+                .map(|name| format!("{{{}}}", name))
+                .join(", "));
+        }
+        if format.is_some() && name != DATE {
+            anyhow::bail!("The {{{}}} placeholder does not take a format", name);
+        }
+        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+        segments.push(Segment::Placeholder { name, format });
+        rest = &rest[end + 1..];
+    }
+    literal.push_str(rest);
+    segments.push(Segment::Literal(literal));
+    Ok(segments)
+}
+
+// Invalid templates are reported when filling them in
+fn contains_any(template: &str, names: &[&str]) -> bool {
+    parse(template).is_ok_and(|segments| segments.iter().any(|segment| {
+        matches!(segment, Segment::Placeholder { name, .. } if names.contains(name))
+    }))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('{', "{{").replace('}', "}}")
+}
+
+fn append_to_file_stem(template: &str, suffix: &str) -> String {
+    let path = std::path::Path::new(template);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => path
+            .with_file_name(format!("{}-{}.{}", stem.to_string_lossy(), suffix, extension.to_string_lossy()))
+            .to_string_lossy()
+            .to_string(),
+        _ => format!("{}-{}", template, suffix),
+    }
+}
+
+// Path separators and control characters would create unexpected directories or unreadable names
+fn sanitize(value: &str, fallback: &str) -> String {
+    let mut sanitized = String::new();
+    for c in value.trim().chars().map(|c| if c == '/' || c.is_control() { '_' } else { c }) {
+        if sanitized.len() + c.len_utf8() > MAX_VALUE_BYTES {
+            break;
+        }
+        sanitized.push(c);
+    }
+    match sanitized.trim() {
+        "" => fallback.to_string(),
+        "." | ".." => "_".to_string(),
+        sanitized => sanitized.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use crate::file_name_template::{distinct_per_window, needs_window, render, with_date, with_sequence_number, with_window};
+    use crate::results::WindowInfo;
+
+    #[test]
+    fn it_should_replace_the_sequence_placeholder() {
+        assert_eq!(with_sequence_number("frames/{seq}.png", 7).unwrap(), "frames/0007.png");
+        assert_eq!(with_sequence_number("capture-{seq}.png", 12345).unwrap(), "capture-12345.png");
+    }
+
+    #[test]
+    fn it_should_append_the_sequence_number_before_the_extension_by_default() {
+        assert_eq!(with_sequence_number("frames/capture.png", 7).unwrap(), "frames/capture-0007.png");
+        assert_eq!(with_sequence_number("capture", 7).unwrap(), "capture-0007");
+    }
+
+    #[test]
+    fn it_should_replace_the_window_placeholders() {
+        let path = with_window("shots/{title}-{class}-{window_id}.png", &window(Some("Terminal"))).unwrap();
+        assert_eq!(path, "shots/Terminal-XTerm-0x3a00007.png");
+        assert_eq!(with_window("{id}.png", &window(None)).unwrap(), "0x3a00007.png");
+    }
+
+    #[test]
+    fn it_should_append_the_window_id_before_the_extension_when_capturing_several_windows() {
+        assert_eq!(distinct_per_window("{title}.png"), "{title}-{window_id}.png");
+        assert_eq!(distinct_per_window("{title}-{id}.png"), "{title}-{id}.png");
+    }
+
+    #[test]
+    fn it_should_make_the_window_values_safe_for_a_file_name() {
+        assert_eq!(with_window("{title}.png", &window(Some("~/src:\tvim\n"))).unwrap(), "~_src:_vim.png");
+        assert_eq!(with_window("{title}.png", &window(Some(".."))).unwrap(), "_.png");
+        assert_eq!(with_window("{title}.png", &window(None)).unwrap(), "untitled.png");
+        assert_eq!(with_window("{title}.png", &window(Some(&"a".repeat(300)))).unwrap().len(), 68);
+    }
+
+    #[test]
+    fn it_should_truncate_the_non_ascii_window_values_on_a_character_boundary() {
+        // 3 bytes per character, so 21 of them fit in the 64 bytes
+        let path = with_window("{title}.png", &window(Some(&"端末".repeat(100)))).unwrap();
+        assert_eq!(path, format!("{}端.png", "端末".repeat(10)));
+        assert_eq!(path.len(), 67);
+    }
+
+    #[test]
+    fn it_should_replace_the_date_placeholders() {
+        let date = Local.with_ymd_and_hms(2024, 5, 1, 12, 34, 56).unwrap();
+        assert_eq!(with_date("{date}.png", &date).unwrap(), "20240501-123456.png");
+        assert_eq!(with_date("{date:%Y/%m/%d}.png", &date).unwrap(), "2024_05_01.png");
+    }
+
+    #[test]
+    fn it_should_reject_invalid_date_formats() {
+        let date = Local.with_ymd_and_hms(2024, 5, 1, 12, 34, 56).unwrap();
+        let error = with_date("{date:%Q}.png", &date).err().unwrap();
+        assert_eq!(error.to_string(), "Invalid date format \"%Q\" in the output file name");
+    }
+
+    #[test]
+    fn it_should_keep_the_placeholders_filled_in_later() {
+        let path = with_sequence_number("{date:%H}-{title}-{seq}.png", 1).unwrap();
+        assert_eq!(path, "{date:%H}-{title}-0001.png");
+        assert!(needs_window(&path));
+    }
+
+    #[test]
+    fn it_should_render_the_escaped_braces() {
+        assert_eq!(render(&with_window("{{{title}}}.png", &window(Some("a}"))).unwrap()).unwrap(), "{a}}.png");
+    }
+
+    #[test]
+    fn it_should_reject_invalid_templates() {
+        let error = render("{name}.png").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unknown placeholder {name} in the output file name, expected one of {date}, {title}, {class}, {window_id}, {id}, {seq}",
+        );
+        assert!(render("{title.png").is_err());
+        assert!(render("{title:%Y}.png").is_err());
+        assert_eq!(
+            render("{seq}.png").err().unwrap().to_string(),
+            "The {seq} placeholder can only be used when taking several screenshots",
+        );
+    }
+
+    fn window(title: Option<&str>) -> WindowInfo {
+        WindowInfo {
+            id: 0x3a00007,
+            title: title.map(str::to_string),
+            instance: Some("xterm".to_string()),
+            class: Some("XTerm".to_string()),
+            pid: None,
+        }
+    }
+}
@@ -1,28 +1,52 @@
-use image::RgbImage;
+use std::cell::RefCell;
+use std::rc::Rc;
 
+use image::DynamicImage;
+
+use crate::animation::AnimationFrame;
 use crate::gateways::FileSystemGateway;
 
+type SavedAnimations = Rc<RefCell<Vec<(Vec<AnimationFrame>, String)>>>;
+
 pub struct FakeFileSystemAdapter {
-    pub vec: Vec<(RgbImage, String)>,
+    /// Shared, so the saved images can still be inspected once the adapter is given to a use case.
+    pub vec: Rc<RefCell<Vec<(DynamicImage, String)>>>,
+    pub animations: SavedAnimations,
     result: Box<dyn Fn() -> anyhow::Result<()>>,
 }
 
 impl FakeFileSystemAdapter {
     pub fn new() -> Self {
         Self {
-            vec: Vec::new(),
+            vec: Rc::new(RefCell::new(Vec::new())),
+            animations: Rc::new(RefCell::new(Vec::new())),
             result: Box::new(|| {Err(anyhow::anyhow!("Unable to save file"))}),
         }
     }
+    /// Saves every file successfully.
+    pub fn saving() -> Self {
+        Self::new().with_result(Box::new(|| Ok(())))
+    }
     pub fn with_result(mut self, result: Box<dyn Fn() -> anyhow::Result<()>>) -> Self {
         self.result = result;
         self
     }
 }
 
+impl Default for FakeFileSystemAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FileSystemGateway for FakeFileSystemAdapter {
-    fn save_image(&mut self, image_buffer: RgbImage, path: &String) -> anyhow::Result<()> {
-        self.vec.push((image_buffer, path.clone()));
+    fn save_image(&mut self, image_buffer: DynamicImage, path: &str) -> anyhow::Result<()> {
+        self.vec.borrow_mut().push((image_buffer, path.to_string()));
+        (self.result)()
+    }
+
+    fn save_animation(&mut self, frames: Vec<AnimationFrame>, path: &str) -> anyhow::Result<()> {
+        self.animations.borrow_mut().push((frames, path.to_string()));
         (self.result)()
     }
 }
\ No newline at end of file
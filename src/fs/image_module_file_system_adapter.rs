@@ -1,17 +1,275 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{Delay, ImageFormat};
+
+use crate::animation::AnimationFrame;
 use crate::gateways::FileSystemGateway;
 
-pub struct ImageModuleFileSystemAdapter {}
+// The default (1) quantizes the colors of large frames much more slowly, for a barely visible difference
+const GIF_ENCODING_SPEED: i32 = 10;
+// The one used by the image crate when the quality is not given
+const DEFAULT_JPEG_QUALITY: u8 = 75;
+
+/// How the images are encoded. The options specific to a format are rejected when saving a file of another format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EncodingOptions {
+    /// The format of files without a known image extension (e.g. `.img`), which has to match the extension otherwise.
+    pub format: Option<ImageFormat>,
+    /// Between 1 and 100.
+    pub jpeg_quality: Option<u8>,
+    pub png_compression: Option<CompressionType>,
+    pub png_filter: Option<FilterType>,
+}
+
+impl EncodingOptions {
+    fn check_applies_to(&self, format: ImageFormat) -> anyhow::Result<()> {
+        if self.jpeg_quality.is_some() && format != ImageFormat::Jpeg {
+            anyhow::bail!("Unable to save file: a quality can only be set for Jpeg files, not for {:?} ones", format);
+        }
+        if (self.png_compression.is_some() || self.png_filter.is_some()) && format != ImageFormat::Png {
+            anyhow::bail!(
+                "Unable to save file: a compression or filter can only be set for Png files, not for {:?} ones",
+                format,
+            );
+        }
+        Ok(())
+    }
+}
+
+pub struct ImageModuleFileSystemAdapter {
+    encoding_options: EncodingOptions,
+}
 
 impl ImageModuleFileSystemAdapter {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            encoding_options: EncodingOptions::default(),
+        }
+    }
+
+    pub fn with_encoding_options(mut self, encoding_options: EncodingOptions) -> Self {
+        self.encoding_options = encoding_options;
+        self
+    }
+}
+
+impl Default for ImageModuleFileSystemAdapter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl FileSystemGateway for ImageModuleFileSystemAdapter {
-    fn save_image(&mut self, image_buffer: image::RgbImage, path: &String) -> anyhow::Result<()> {
-        image_buffer
-            .save(path)
-            .map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))
+    fn save_image(&mut self, image_buffer: image::DynamicImage, path: &str) -> anyhow::Result<()> {
+        let format = match (self.encoding_options.format, ImageFormat::from_path(path)) {
+            (Some(format), Ok(extension_format)) if format != extension_format => anyhow::bail!(
+                "Unable to save file: the {:?} format does not match the extension of {:?}",
+                format,
+                path,
+            ),
+            (Some(format), _) => format,
+            (None, extension_format) => extension_format
+                .map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))?,
+        };
+        self.encoding_options.check_applies_to(format)?;
+        if image_buffer.color().has_alpha() && !supports_alpha(format) {
+            anyhow::bail!(
+                "Unable to save file: transparency can only be kept in PNG, WebP, TIFF, BMP or QOI files, not in {:?} ones",
+                format,
+            );
+        }
+        let file = File::create(path).map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))?;
+        let mut writer = BufWriter::new(file);
+        let encoded = match format {
+            ImageFormat::Jpeg => image_buffer.write_with_encoder(JpegEncoder::new_with_quality(
+                &mut writer,
+                self.encoding_options.jpeg_quality.unwrap_or(DEFAULT_JPEG_QUALITY),
+            )),
+            ImageFormat::Png => image_buffer.write_with_encoder(PngEncoder::new_with_quality(
+                &mut writer,
+                self.encoding_options.png_compression.unwrap_or_default(),
+                self.encoding_options.png_filter.unwrap_or_default(),
+            )),
+            // Lossless for WebP, which is the only kind of WebP encoding supported by the image crate
+            _ => image_buffer.write_to(&mut writer, format),
+        };
+        encoded.map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))?;
+        writer.flush().map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))
+    }
+
+    fn save_animation(&mut self, frames: Vec<AnimationFrame>, path: &str) -> anyhow::Result<()> {
+        let extension = Path::new(path).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        let encode = match extension.as_deref() {
+            Some("gif") => save_gif,
+            Some("png") | Some("apng") => save_apng,
+            _ => anyhow::bail!(
+                "Unable to save file: animations can only be saved as GIF or APNG files (.gif, .png or .apng), not {:?}",
+                path,
+            ),
+        };
+        let file = File::create(path).map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))?;
+        encode(BufWriter::new(file), frames).map_err(|e| anyhow::anyhow!("Unable to save file: {:?}", e))
+    }
+}
+
+fn supports_alpha(format: ImageFormat) -> bool {
+    matches!(format, ImageFormat::Png | ImageFormat::WebP | ImageFormat::Tiff | ImageFormat::Bmp | ImageFormat::Qoi)
+}
+
+fn save_gif(writer: BufWriter<File>, frames: Vec<AnimationFrame>) -> anyhow::Result<()> {
+    let mut encoder = GifEncoder::new_with_speed(writer, GIF_ENCODING_SPEED);
+    encoder.set_repeat(Repeat::Infinite)?;
+    encoder.encode_frames(frames.into_iter().map(|frame| {
+        image::Frame::from_parts(frame.image, 0, 0, Delay::from_saturating_duration(frame.delay))
+    }))?;
+    Ok(())
+}
+
+fn save_apng(writer: BufWriter<File>, frames: Vec<AnimationFrame>) -> anyhow::Result<()> {
+    let Some(first) = frames.first() else {
+        anyhow::bail!("no frame to save");
+    };
+    let mut encoder = png::Encoder::new(writer, first.image.width(), first.image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+    let mut writer = encoder.write_header()?;
+    for frame in &frames {
+        // In milliseconds, the longest delay representable with a denominator of 1000
+        let delay = frame.delay.as_millis().min(u16::MAX as u128) as u16;
+        writer.set_frame_delay(delay, 1000)?;
+        writer.write_image_data(frame.image.as_raw())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use image::codecs::png::CompressionType;
+    use image::{DynamicImage, ImageFormat, ImageReader};
+
+    use crate::fs::image_module_file_system_adapter::{EncodingOptions, ImageModuleFileSystemAdapter};
+    use crate::gateways::FileSystemGateway;
+
+    #[test]
+    fn it_should_save_jpeg_files_with_the_given_quality() {
+        // Given
+        let temp_dir = TempDir::new("jpeg_quality");
+        let low_quality_path = temp_dir.path("low_quality.jpg");
+        let high_quality_path = temp_dir.path("high_quality.jpg");
+
+        // When
+        save(EncodingOptions { jpeg_quality: Some(10), ..EncodingOptions::default() }, &low_quality_path).unwrap();
+        save(EncodingOptions { jpeg_quality: Some(95), ..EncodingOptions::default() }, &high_quality_path).unwrap();
+
+        // Then
+        let low_quality_size = std::fs::metadata(&low_quality_path).unwrap().len();
+        let high_quality_size = std::fs::metadata(&high_quality_path).unwrap().len();
+        assert!(low_quality_size < high_quality_size, "{} >= {}", low_quality_size, high_quality_size);
+    }
+
+    #[test]
+    fn it_should_save_files_without_image_extension_in_the_given_format() {
+        // Given
+        let temp_dir = TempDir::new("format");
+        let path = temp_dir.path("capture.img");
+
+        // When
+        let result = save(EncodingOptions { format: Some(ImageFormat::Qoi), ..EncodingOptions::default() }, &path);
+
+        // Then
+        assert!(result.is_ok());
+        let reader = ImageReader::open(&path).unwrap().with_guessed_format().unwrap();
+        assert_eq!(reader.format(), Some(ImageFormat::Qoi));
+    }
+
+    #[test]
+    fn it_should_reject_a_format_not_matching_the_extension() {
+        // Given
+        let temp_dir = TempDir::new("format_mismatch");
+        let path = temp_dir.path("capture.png");
+
+        // When
+        let result = save(EncodingOptions { format: Some(ImageFormat::Qoi), ..EncodingOptions::default() }, &path);
+
+        // Then
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            format!("Unable to save file: the Qoi format does not match the extension of {:?}", path),
+        );
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[test]
+    fn it_should_reject_the_options_of_another_format() {
+        // Given
+        let options = EncodingOptions { png_compression: Some(CompressionType::Best), ..EncodingOptions::default() };
+
+        // When
+        let result = save(options, &TempDir::new("other_format_options").path("capture.webp"));
+
+        // Then
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Unable to save file: a compression or filter can only be set for Png files, not for WebP ones",
+        );
+    }
+
+    #[test]
+    fn it_should_reject_transparency_in_formats_without_alpha_channel() {
+        // Given
+        let mut adapter = ImageModuleFileSystemAdapter::new();
+        let image_buffer = DynamicImage::ImageRgba8(image::RgbaImage::new(1, 1));
+
+        // When
+        let result = adapter.save_image(image_buffer, &TempDir::new("alpha").path("capture.jpg"));
+
+        // Then
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Unable to save file: transparency can only be kept in PNG, WebP, TIFF, BMP or QOI files, not in Jpeg ones",
+        );
+    }
+
+    // Noise, so that the size of the encoded files depends on the encoding options
+    fn save(options: EncodingOptions, path: &str) -> anyhow::Result<()> {
+        let image_buffer = image::RgbImage::from_fn(64, 64, |x, y| {
+            let value = (x * 7919 + y * 104729) % 251;
+            image::Rgb([value as u8, (value * 3 % 256) as u8, (value * 7 % 256) as u8])
+        });
+        ImageModuleFileSystemAdapter::new()
+            .with_encoding_options(options)
+            .save_image(DynamicImage::ImageRgb8(image_buffer), path)
     }
-}
\ No newline at end of file
+
+    // A directory of its own per test, removed along with the files saved in it
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("borkscreen-{}-{}", std::process::id(), name));
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn path(&self, file_name: &str) -> String {
+            self.0.join(file_name).to_string_lossy().to_string()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+}
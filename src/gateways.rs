@@ -1,19 +1,50 @@
-use crate::results::ResultType;
+use std::time::{Duration, Instant};
 
-pub trait ScreenShotWindowSystemGateway {
-    fn find_window(&self, searched_window_name: &String) -> anyhow::Result<Option<u64>>;
-    fn take_screen_shot(&self, window_id: u64) -> anyhow::Result<image::RgbImage>;
+use crate::animation::AnimationFrame;
+use crate::cursor::Cursor;
+use crate::region::Region;
+use crate::results::{MonitorInfo, ResultType, WindowInfo};
+use crate::window_selector::WindowSelector;
+
+pub trait ScreenShotWindowSystemGateway: ListMonitorsWindowSystemGateway {
+    fn find_window(&self, selector: &WindowSelector) -> anyhow::Result<Option<u64>>;
+    /// Every matching window, in the same order as the one `find_window` returns the first of.
+    fn find_windows(&self, selector: &WindowSelector) -> anyhow::Result<Vec<WindowInfo>>;
+    fn get_active_window(&self) -> anyhow::Result<Option<u64>>;
+    fn pick_window(&self) -> anyhow::Result<Option<u64>>;
+    fn get_root_window(&self, screen: Option<i32>) -> anyhow::Result<u64>;
+    fn get_frame_window(&self, window_id: u64) -> anyhow::Result<u64>;
+    fn window_exists(&self, window_id: u64) -> anyhow::Result<bool>;
+    fn get_window_info(&self, window_id: u64) -> anyhow::Result<WindowInfo>;
+    fn is_window_mapped(&self, window_id: u64) -> anyhow::Result<bool>;
+    fn take_screen_shot(&self, window_id: u64, region: Option<&Region>) -> anyhow::Result<image::RgbaImage>;
+    fn get_cursor(&self, window_id: u64) -> anyhow::Result<Cursor>;
+    /// Returns false if the content of the window did not change within the timeout.
+    fn wait_for_damage(&self, window_id: u64, timeout: Duration) -> anyhow::Result<bool>;
 }
 
 pub trait ListWindowsWindowSystemGateway {
-    fn list_windows(&self) -> anyhow::Result<Vec<String>>;
+    fn list_windows(&self) -> anyhow::Result<Vec<WindowInfo>>;
+}
+
+pub trait ListMonitorsWindowSystemGateway {
+    fn list_monitors(&self) -> anyhow::Result<Vec<MonitorInfo>>;
 }
 
 pub trait FileSystemGateway {
-    fn save_image(&mut self, image_buffer: image::RgbImage, path: &String) -> anyhow::Result<()>;
+    fn save_image(&mut self, image_buffer: image::DynamicImage, path: &str) -> anyhow::Result<()>;
+    fn save_animation(&mut self, frames: Vec<AnimationFrame>, path: &str) -> anyhow::Result<()>;
+}
+
+pub trait ClockGateway {
+    fn now(&self) -> Instant;
+    /// The wall-clock time, for the file names.
+    fn date_time(&self) -> chrono::DateTime<chrono::Local>;
+    fn sleep(&self, duration: Duration);
 }
 
 pub trait PresenterGateway {
     fn present_error(&self, cause: String) -> anyhow::Result<()>;
     fn present_result(&self, result: &ResultType) -> anyhow::Result<()>;
+    fn present_countdown(&self, remaining_seconds: u64) -> anyhow::Result<()>;
 }
\ No newline at end of file
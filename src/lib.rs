@@ -1,9 +1,23 @@
 pub mod take_screen_shot_usecase;
 pub mod list_windows_usecase;
+pub mod list_monitors_usecase;
+pub mod timelapse_usecase;
+pub mod record_usecase;
+pub mod watch_usecase;
 pub mod presenter;
 pub mod results;
 pub mod gateways;
+pub mod window_matcher;
+pub mod window_selector;
+pub mod region;
+pub mod monitor_selector;
+pub mod cursor;
+pub mod animation;
+pub mod duration;
+pub mod file_name_template;
+pub mod stop_request;
 
 pub mod window_system;
 pub mod fs;
+pub mod clock;
 pub mod presenter_adapter;
@@ -0,0 +1,84 @@
+use crate::gateways::ListMonitorsWindowSystemGateway;
+use crate::results::ResultType;
+
+pub struct ListMonitorsUseCase {
+    window_system_gateway: Box<dyn ListMonitorsWindowSystemGateway>,
+}
+
+impl ListMonitorsUseCase {
+    pub fn new(window_system_gateway: Box<dyn ListMonitorsWindowSystemGateway>) -> Self {
+        Self {
+            window_system_gateway,
+        }
+    }
+
+    pub fn execute(&self) -> anyhow::Result<ResultType> {
+        let monitors = self.window_system_gateway.list_monitors()?;
+        Ok(ResultType::ListMonitorsResult(monitors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::list_monitors_usecase::ListMonitorsUseCase;
+    use crate::results::{MonitorInfo, ResultType};
+    use crate::window_system::fake_window_system_adapter::FakeWindowSystemAdapter;
+
+    #[test]
+    fn it_should_report_listing_monitor_failures() {
+        // Given
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new());
+        let usecase = ListMonitorsUseCase::new(
+            window_system_gateway,
+        );
+
+        // When
+        let result = usecase.execute();
+
+        // Then
+        assert_error(result, "Unable to list monitors.");
+    }
+
+    #[test]
+    fn it_should_work() {
+        // Given
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_list_monitors_result(|| Ok(vec![monitor("DP-1", 0), monitor("DP-2", 1920)])));
+        let usecase = ListMonitorsUseCase::new(
+            window_system_gateway,
+        );
+
+        // When
+        let result = usecase.execute();
+
+        // Then
+        match result.unwrap() {
+            ResultType::ListMonitorsResult(monitors) => {
+                assert_eq!(monitors, vec![monitor("DP-1", 0), monitor("DP-2", 1920)]);
+            }
+            _ => panic!("Expected ListMonitorsResult"),
+        }
+    }
+
+    fn monitor(name: &str, x: u32) -> MonitorInfo {
+        MonitorInfo {
+            name: name.to_string(),
+            primary: x == 0,
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    pub fn assert_error<T>(
+        result: anyhow::Result<T>,
+        expected_msg: &str,
+    ) {
+        if let Err(e) = result {
+            assert_eq!(e.to_string(), expected_msg);
+        } else {
+            panic!("Expected an error, but got a success result");
+        }
+    }
+}
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::gateways::ListWindowsWindowSystemGateway;
 use crate::results::ResultType;
+use crate::window_selector::WindowSelector;
 
 pub struct ListWindowsUseCase {
     window_system_gateway: Box<dyn ListWindowsWindowSystemGateway>,
@@ -12,8 +13,14 @@ impl ListWindowsUseCase {
         }
     }
 
-    pub fn execute(&self) -> anyhow::Result<ResultType> {
-        let windows = self.window_system_gateway.list_windows()?;
+    pub fn execute(&self, filter: Option<WindowSelector>) -> anyhow::Result<ResultType> {
+        let windows = self.window_system_gateway.list_windows()?
+            .into_iter()
+            .filter(|window| match &filter {
+                Some(selector) => selector.matches(window),
+                None => true,
+            })
+            .collect();
         Ok(ResultType::ListWindowResult(windows))
     }
 }
@@ -21,7 +28,9 @@ impl ListWindowsUseCase {
 #[cfg(test)]
 mod tests {
     use crate::list_windows_usecase::ListWindowsUseCase;
-    use crate::results::ResultType;
+    use crate::results::{ResultType, WindowInfo};
+    use crate::window_matcher::{MatchMode, WindowMatcher};
+    use crate::window_selector::WindowSelector;
     use crate::window_system::fake_window_system_adapter::FakeWindowSystemAdapter;
 
     #[test]
@@ -33,7 +42,7 @@ mod tests {
         );
 
         // When
-        let result = when(&mut usecase);
+        let result = when(&mut usecase, None);
 
         // Then
         assert_error(result, "Unable to list windows.");
@@ -44,26 +53,60 @@ mod tests {
         // Given
         let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
             .with_list_windows_result(|| Ok(vec![
-                "window1".to_string(), "window2".to_string()
+                window("window1", "feh"), window("window2", "feh")
             ])));
         let mut usecase = ListWindowsUseCase::new(
             window_system_gateway,
         );
 
         // When
-        let result = when(&mut usecase);
+        let result = when(&mut usecase, None);
 
         // Then
         match result.unwrap() {
             ResultType::ListWindowResult(windows) => {
-                assert_eq!(windows, vec!["window1".to_string(), "window2".to_string()]);
+                assert_eq!(windows, vec![window("window1", "feh"), window("window2", "feh")]);
             }
             _ => panic!("Expected ListWindowResult"),
         }
     }
 
-    fn when(usecase: &mut ListWindowsUseCase) -> anyhow::Result<ResultType> {
-        usecase.execute()
+    #[test]
+    fn it_should_only_list_the_windows_matching_the_filter() {
+        // Given
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_list_windows_result(|| Ok(vec![
+                window("window1", "feh"), window("bash", "XTerm"), window("window2", "feh")
+            ])));
+        let mut usecase = ListWindowsUseCase::new(
+            window_system_gateway,
+        );
+        let filter = WindowSelector::Class(WindowMatcher::new("feh", MatchMode::Exact).unwrap());
+
+        // When
+        let result = when(&mut usecase, Some(filter));
+
+        // Then
+        match result.unwrap() {
+            ResultType::ListWindowResult(windows) => {
+                assert_eq!(windows, vec![window("window1", "feh"), window("window2", "feh")]);
+            }
+            _ => panic!("Expected ListWindowResult"),
+        }
+    }
+
+    fn window(title: &str, class: &str) -> WindowInfo {
+        WindowInfo {
+            id: 1,
+            title: Some(title.to_string()),
+            instance: Some(class.to_lowercase()),
+            class: Some(class.to_string()),
+            pid: None,
+        }
+    }
+
+    fn when(usecase: &mut ListWindowsUseCase, filter: Option<WindowSelector>) -> anyhow::Result<ResultType> {
+        usecase.execute(filter)
     }
 
     pub fn assert_error<T>(
@@ -1,12 +1,28 @@
-use borkscreen::fs::image_module_file_system_adapter::ImageModuleFileSystemAdapter;
-use borkscreen::take_screen_shot_usecase::TakeScreenShotUseCase;
+use std::sync::Arc;
+use std::time::Duration;
+
+use borkscreen::clock::system_clock_adapter::SystemClockAdapter;
+use borkscreen::duration::{parse_duration, parse_positive_duration};
+use borkscreen::fs::image_module_file_system_adapter::{EncodingOptions, ImageModuleFileSystemAdapter};
+use borkscreen::monitor_selector::MonitorSelector;
+use borkscreen::record_usecase::RecordUseCase;
+use borkscreen::region::Region;
+use borkscreen::stop_request::StopRequest;
+use borkscreen::timelapse_usecase::TimelapseUseCase;
+use borkscreen::take_screen_shot_usecase::{CaptureOptions, SettleOptions, TakeScreenShotUseCase};
+use borkscreen::watch_usecase::WatchUseCase;
 use borkscreen::window_system::x11_dl_window_system_adapter::X11DLWindowSystemAdapter;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use image::codecs::png::{CompressionType, FilterType};
+use image::ImageFormat;
 use borkscreen::gateways::PresenterGateway;
+use borkscreen::list_monitors_usecase::ListMonitorsUseCase;
 use borkscreen::list_windows_usecase::ListWindowsUseCase;
 use borkscreen::presenter::Presenter;
 use borkscreen::presenter_adapter::plain_text_presenter_adapter::PlainTextPresenterAdapter;
 use borkscreen::presenter_adapter::serde_presenter_adapter::SerdePresenterAdapter;
+use borkscreen::window_matcher::{MatchMode, WindowMatcher};
+use borkscreen::window_selector::{CaptureTarget, WindowSelector};
 
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
 enum OutputFormat {
@@ -14,6 +30,73 @@ enum OutputFormat {
     Json,
 }
 
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+enum MatchModeArg {
+    Exact,
+    Substring,
+    CaseInsensitive,
+    Regex,
+}
+
+impl From<MatchModeArg> for MatchMode {
+    fn from(value: MatchModeArg) -> Self {
+        match value {
+            MatchModeArg::Exact => MatchMode::Exact,
+            MatchModeArg::Substring => MatchMode::Substring,
+            MatchModeArg::CaseInsensitive => MatchMode::CaseInsensitive,
+            MatchModeArg::Regex => MatchMode::Regex,
+        }
+    }
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+enum ImageFormatArg {
+    Png,
+    Jpeg,
+    Webp,
+    Tiff,
+    Bmp,
+    Pnm,
+    Qoi,
+}
+
+impl From<ImageFormatArg> for ImageFormat {
+    fn from(value: ImageFormatArg) -> Self {
+        match value {
+            ImageFormatArg::Png => ImageFormat::Png,
+            ImageFormatArg::Jpeg => ImageFormat::Jpeg,
+            ImageFormatArg::Webp => ImageFormat::WebP,
+            ImageFormatArg::Tiff => ImageFormat::Tiff,
+            ImageFormatArg::Bmp => ImageFormat::Bmp,
+            ImageFormatArg::Pnm => ImageFormat::Pnm,
+            ImageFormatArg::Qoi => ImageFormat::Qoi,
+        }
+    }
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+enum PngFilterArg {
+    None,
+    Sub,
+    Up,
+    Avg,
+    Paeth,
+    Adaptive,
+}
+
+impl From<PngFilterArg> for FilterType {
+    fn from(value: PngFilterArg) -> Self {
+        match value {
+            PngFilterArg::None => FilterType::NoFilter,
+            PngFilterArg::Sub => FilterType::Sub,
+            PngFilterArg::Up => FilterType::Up,
+            PngFilterArg::Avg => FilterType::Avg,
+            PngFilterArg::Paeth => FilterType::Paeth,
+            PngFilterArg::Adaptive => FilterType::Adaptive,
+        }
+    }
+}
+
 #[derive(Parser)]
 #[command(name = "borkscreen")]
 #[command(version = "0.0.0")]
@@ -27,43 +110,436 @@ struct Cli {
     command: Commands,
 }
 
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct CaptureTargetArgs {
+    #[arg(short, long)]
+    window_title: Option<String>,
+
+    /// Instance or class name of the WM_CLASS property of the window
+    #[arg(short, long)]
+    class: Option<String>,
+
+    /// X window id of the window, as displayed by the `list` subcommand (e.g. 0x3a00007)
+    #[arg(long, value_parser = parse_window_id)]
+    window_id: Option<u64>,
+
+    /// Process id of the application owning the window (read from the _NET_WM_PID property)
+    #[arg(short, long)]
+    pid: Option<u32>,
+
+    /// Captures the currently focused window
+    #[arg(long)]
+    active: bool,
+
+    /// Lets the user click on the window to capture (any other button than the left one cancels)
+    #[arg(long)]
+    pick: bool,
+
+    /// Captures the whole screen with the given number (the default screen if omitted)
+    #[arg(long, value_name = "SCREEN")]
+    screen: Option<Option<i32>>,
+
+    /// Captures the monitor with the given name, index (as displayed by the `monitors` subcommand) or the primary one
+    #[arg(long, value_name = "NAME|INDEX|primary")]
+    monitor: Option<MonitorSelector>,
+}
+
+#[derive(Args)]
+struct EncodingArgs {
+    /// Format of the output file, deduced from its extension if omitted. It has to match a known image extension.
+    /// WebP files are lossless
+    #[arg(long, value_enum)]
+    format: Option<ImageFormatArg>,
+
+    /// Quality of JPEG files, between 1 and 100
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100))]
+    jpeg_quality: Option<u8>,
+
+    /// Compression of PNG files: fast, default, best, none or a level between 1 and 9
+    #[arg(long, value_parser = parse_png_compression)]
+    png_compression: Option<CompressionType>,
+
+    /// Filter applied to the rows of PNG files before compressing them
+    #[arg(long, value_enum)]
+    png_filter: Option<PngFilterArg>,
+}
+
+impl From<&EncodingArgs> for EncodingOptions {
+    fn from(value: &EncodingArgs) -> Self {
+        EncodingOptions {
+            format: value.format.map(Into::into),
+            jpeg_quality: value.jpeg_quality,
+            png_compression: value.png_compression,
+            png_filter: value.png_filter.map(Into::into),
+        }
+    }
+}
+
+/// The target of the captures and the options shared by every capturing command
+#[derive(Args)]
+struct CaptureArgs {
+    #[command(flatten)]
+    target: CaptureTargetArgs,
+
+    /// How the window title or class is compared to the ones of the existing windows
+    #[arg(short, long, default_value_t = MatchModeArg::Exact, value_enum)]
+    match_mode: MatchModeArg,
+
+    /// Also looks for the windows owned by the child processes of the given pid
+    #[arg(long, requires = "pid")]
+    include_children: bool,
+
+    /// Only captures the WIDTHxHEIGHT+X+Y region of the window, screen or monitor (e.g. 640x480+10+20)
+    #[arg(short, long)]
+    geometry: Option<Region>,
+
+    /// Includes the title bar and borders drawn by the window manager
+    #[arg(long)]
+    with_frame: bool,
+
+    /// Draws the mouse cursor onto the captures
+    #[arg(long)]
+    cursor: bool,
+
+    /// Waits for the searched window to appear for at most this long, e.g. 10s
+    #[arg(long, value_parser = parse_duration)]
+    wait_timeout: Option<Duration>,
+}
+
+impl CaptureArgs {
+    fn to_capture_target(&self) -> anyhow::Result<CaptureTarget> {
+        let target = &self.target;
+        if let Some(monitor) = &target.monitor {
+            return Ok(CaptureTarget::Monitor(monitor.clone()));
+        }
+        if let Some(screen) = target.screen {
+            return Ok(CaptureTarget::Screen(screen));
+        }
+        if target.pick {
+            return Ok(CaptureTarget::Pick);
+        }
+        if target.active {
+            return Ok(CaptureTarget::Active);
+        }
+        if let Some(window_id) = target.window_id {
+            return Ok(CaptureTarget::WindowId(window_id));
+        }
+        if let Some(pid) = target.pid {
+            return Ok(CaptureTarget::Search(WindowSelector::Pid { pid, include_children: self.include_children }));
+        }
+        let match_mode = self.match_mode.into();
+        if let Some(class) = &target.class {
+            return Ok(CaptureTarget::Search(WindowSelector::Class(WindowMatcher::new(class, match_mode)?)));
+        }
+        let window_title = target.window_title.as_deref().unwrap_or_default();
+        Ok(CaptureTarget::Search(WindowSelector::Title(WindowMatcher::new(window_title, match_mode)?)))
+    }
+
+    /// The options specific to a command are left to their default.
+    fn to_capture_options(&self) -> CaptureOptions {
+        CaptureOptions {
+            region: self.geometry,
+            with_frame: self.with_frame,
+            cursor: self.cursor,
+            wait_timeout: self.wait_timeout,
+            ..CaptureOptions::default()
+        }
+    }
+}
+
+#[derive(Args)]
+struct OffscreenArgs {
+    /// Captures the window as if it was fully visible, even if obscured by other windows or partially
+    /// off-screen (Composite extension). The window is repainted for each capture
+    #[arg(long)]
+    offscreen: bool,
+}
+
+/// The options of the commands saving still images
+#[derive(Args)]
+struct ImageArgs {
+    /// Keeps the transparency of ARGB windows (only for PNG, WebP, TIFF, BMP and QOI files)
+    #[arg(long)]
+    keep_alpha: bool,
+
+    #[command(flatten)]
+    encoding: EncodingArgs,
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    /// Captures a screenshot of a window given its title
+    /// Captures a screenshot of a window or of the whole screen
     Capture {
+        #[command(flatten)]
+        capture: CaptureArgs,
+
+        #[command(flatten)]
+        offscreen: OffscreenArgs,
+
+        #[command(flatten)]
+        image: ImageArgs,
+
+        /// Waits before taking the screenshot, e.g. 5s or 500ms
+        #[arg(long, value_parser = parse_duration)]
+        delay: Option<Duration>,
+
+        /// Captures repeatedly until the content of the window stops changing (e.g. startup animations)
+        #[arg(long)]
+        settle: bool,
+
+        /// Number of consecutive similar captures required to consider the content settled
+        #[arg(long, requires = "settle", default_value_t = 3, value_parser = clap::value_parser!(u32).range(2..))]
+        settle_frames: u32,
+
+        /// Fraction of pixels (between 0 and 1) allowed to differ between two similar captures
+        #[arg(long, requires = "settle", default_value_t = 0.0, value_parser = parse_threshold)]
+        settle_threshold: f64,
+
+        /// Fails if the content is still changing after this long
+        #[arg(long, requires = "settle", default_value = "5s", value_parser = parse_duration)]
+        settle_timeout: Duration,
+
+        /// Captures periodically (until interrupted with Ctrl-C), e.g. 2s. The output file name can contain {seq}
+        #[arg(long, value_parser = parse_positive_duration)]
+        interval: Option<Duration>,
+
+        /// Stops the periodic capture after this number of screenshots
+        #[arg(long, requires = "interval", value_parser = clap::value_parser!(u32).range(1..))]
+        count: Option<u32>,
+
+        /// Displays the remaining seconds of the delay
+        #[arg(long, requires = "delay")]
+        countdown: bool,
+
+        /// Captures every window matching the title, class or pid, each one into its own file
+        #[arg(long, conflicts_with_all = ["window_id", "active", "pick", "screen", "monitor", "interval"])]
+        all: bool,
+
+        /// Can contain {date} or {date:FORMAT} (strftime), {title}, {class}, {window_id} and {seq} placeholders
         #[arg(short, long)]
-        window_title: String,
+        output_file: String,
+    },
+    /// Records a window, the whole screen or a monitor into an animated GIF or APNG file
+    Record {
+        #[command(flatten)]
+        capture: CaptureArgs,
 
+        #[command(flatten)]
+        offscreen: OffscreenArgs,
+
+        /// Number of captures per second
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=50))]
+        fps: u32,
+
+        /// Length of the recording (it can be stopped earlier with Ctrl-C), e.g. 10s
+        #[arg(long, value_parser = parse_duration)]
+        duration: Duration,
+
+        /// The format is deduced from the extension: .gif, or .png or .apng for an animated PNG.
+        /// Can contain {date} or {date:FORMAT} (strftime), {title}, {class} and {window_id} placeholders
+        #[arg(short, long)]
+        output_file: String,
+    },
+    /// Captures a window, the whole screen or a monitor each time its content changes (Damage extension)
+    Watch {
+        #[command(flatten)]
+        capture: CaptureArgs,
+
+        #[command(flatten)]
+        image: ImageArgs,
+
+        /// Only captures once the content has not changed for this long, e.g. 500ms
+        #[arg(long, value_parser = parse_duration)]
+        debounce: Option<Duration>,
+
+        /// Stops after this number of screenshots (the initial one included)
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        count: Option<u32>,
+
+        /// Can contain {date} or {date:FORMAT} (strftime), {title}, {class}, {window_id} and {seq} placeholders
         #[arg(short, long)]
         output_file: String,
     },
     /// Lists all windows
-    List,
+    List {
+        /// Only lists the windows whose WM_CLASS instance or class name matches
+        #[arg(short, long)]
+        class: Option<String>,
+
+        /// How the class is compared to the ones of the existing windows
+        #[arg(short, long, default_value_t = MatchModeArg::Exact, value_enum)]
+        match_mode: MatchModeArg,
+    },
+    /// Lists all monitors
+    Monitors,
 }
 
-fn main() {
-    let cli = Cli::parse();
-    let presenter_adapter: Box<dyn PresenterGateway> = match cli.output_format { 
+fn parse_window_id(value: &str) -> Result<u64, String> {
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| format!("{:?} is not a valid window id", value))
+}
+
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(threshold) if (0.0..=1.0).contains(&threshold) => Ok(threshold),
+        _ => Err(format!("{:?} is not a number between 0 and 1", value)),
+    }
+}
+
+fn parse_png_compression(value: &str) -> Result<CompressionType, String> {
+    match value {
+        "fast" => Ok(CompressionType::Fast),
+        "default" => Ok(CompressionType::Default),
+        "best" => Ok(CompressionType::Best),
+        "none" => Ok(CompressionType::Uncompressed),
+        _ => match value.parse::<u8>() {
+            Ok(level) if (1..=9).contains(&level) => Ok(CompressionType::Level(level)),
+            _ => Err(format!("{:?} is not fast, default, best, none or a level between 1 and 9", value)),
+        },
+    }
+}
+
+fn create_presenter_adapter(output_format: OutputFormat) -> Box<dyn PresenterGateway> {
+    match output_format {
         OutputFormat::PlainText => Box::new(PlainTextPresenterAdapter::new()),
         OutputFormat::Json => Box::new(SerdePresenterAdapter::new())
-    };
-    let presenter = Presenter::new(presenter_adapter);
+    }
+}
+
+/// Returns the stop request made by Ctrl-C once the capture loop has started. Until then, Ctrl-C interrupts the
+/// process as usual.
+fn stop_on_ctrl_c() -> Arc<StopRequest> {
+    let stop_request = Arc::new(StopRequest::new());
+    let handler_stop_request = stop_request.clone();
+    ctrlc::set_handler(move || {
+        if !handler_stop_request.request() {
+            // The exit status of a process killed by SIGINT, as reported by the shells
+            std::process::exit(130);
+        }
+    }).expect("Unable to set the Ctrl-C handler.");
+    stop_request
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let presenter = Presenter::new(create_presenter_adapter(cli.output_format));
 
     let command_result = match &cli.command {
-        Commands::Capture { window_title, output_file } => {
+        Commands::Capture {
+            capture,
+            offscreen,
+            image,
+            delay,
+            settle,
+            settle_frames,
+            settle_threshold,
+            settle_timeout,
+            interval,
+            count,
+            countdown,
+            all,
+            output_file,
+        } => {
             let mut usecase = TakeScreenShotUseCase::new(
-                Box::new(X11DLWindowSystemAdapter::new().expect("Unable to create X11DLWindowSystemAdapter.")),
-                Box::new(ImageModuleFileSystemAdapter::new()),
+                Box::new(X11DLWindowSystemAdapter::new()
+                    .expect("Unable to create X11DLWindowSystemAdapter.")
+                    .with_offscreen_capture(offscreen.offscreen)),
+                Box::new(ImageModuleFileSystemAdapter::new().with_encoding_options((&image.encoding).into())),
+                Box::new(SystemClockAdapter::new()),
             );
-            usecase.take_screenshot(
-                window_title.to_string(),
-                output_file.to_string(),
-            )
+            if *countdown {
+                usecase = usecase.with_countdown(create_presenter_adapter(cli.output_format));
+            }
+            let options = CaptureOptions {
+                keep_alpha: image.keep_alpha,
+                delay: *delay,
+                settle: settle.then(|| SettleOptions {
+                    frames: *settle_frames,
+                    threshold: *settle_threshold,
+                    timeout: *settle_timeout,
+                }),
+                ..capture.to_capture_options()
+            };
+            let target = capture.to_capture_target();
+            if *all {
+                target.and_then(|target| match target {
+                    CaptureTarget::Search(selector) => usecase.take_screenshots_of_all_windows(
+                        selector,
+                        options,
+                        output_file,
+                    ),
+                    _ => unreachable!("--all conflicts with the other kinds of targets"),
+                })
+            } else if let Some(interval) = interval {
+                let mut usecase = TimelapseUseCase::new(usecase, stop_on_ctrl_c());
+                target.and_then(|target| usecase.execute(target, options, *interval, *count, output_file))
+            } else {
+                target.and_then(|target| usecase.take_screenshot(
+                    target,
+                    options,
+                    output_file.to_string(),
+                ))
+            }
         }
-        Commands::List => {
+        Commands::Record {
+            capture,
+            offscreen,
+            fps,
+            duration,
+            output_file,
+        } => {
+            let mut usecase = RecordUseCase::new(
+                TakeScreenShotUseCase::new(
+                    Box::new(X11DLWindowSystemAdapter::new()
+                        .expect("Unable to create X11DLWindowSystemAdapter.")
+                        .with_offscreen_capture(offscreen.offscreen)),
+                    Box::new(ImageModuleFileSystemAdapter::new()),
+                    Box::new(SystemClockAdapter::new()),
+                ),
+                stop_on_ctrl_c(),
+            );
+            capture.to_capture_target()
+                .and_then(|target| usecase.execute(target, capture.to_capture_options(), *fps, *duration, output_file))
+        }
+        Commands::Watch {
+            capture,
+            image,
+            debounce,
+            count,
+            output_file,
+        } => {
+            let mut usecase = WatchUseCase::new(
+                TakeScreenShotUseCase::new(
+                    Box::new(X11DLWindowSystemAdapter::new().expect("Unable to create X11DLWindowSystemAdapter.")),
+                    Box::new(ImageModuleFileSystemAdapter::new().with_encoding_options((&image.encoding).into())),
+                    Box::new(SystemClockAdapter::new()),
+                ),
+                stop_on_ctrl_c(),
+            );
+            let options = CaptureOptions {
+                keep_alpha: image.keep_alpha,
+                ..capture.to_capture_options()
+            };
+            capture.to_capture_target()
+                .and_then(|target| usecase.execute(target, options, *debounce, *count, output_file))
+        }
+        Commands::List { class, match_mode } => {
             let usecase = ListWindowsUseCase::new(
                 Box::new(X11DLWindowSystemAdapter::new().expect("Unable to create X11DLWindowSystemAdapter.")),
             );
+            class.as_ref()
+                .map(|class| WindowMatcher::new(class, (*match_mode).into()).map(WindowSelector::Class))
+                .transpose()
+                .and_then(|filter| usecase.execute(filter))
+        }
+        Commands::Monitors => {
+            let usecase = ListMonitorsUseCase::new(
+                Box::new(X11DLWindowSystemAdapter::new().expect("Unable to create X11DLWindowSystemAdapter.")),
+            );
             usecase.execute()
         }
     };
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::results::MonitorInfo;
+
+/// Designates a monitor by its output name (e.g. `DP-1`), its index in the monitor list or as the
+/// primary one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MonitorSelector {
+    Name(String),
+    Index(usize),
+    Primary,
+}
+
+impl MonitorSelector {
+    pub fn select<'a>(&self, monitors: &'a [MonitorInfo]) -> Option<&'a MonitorInfo> {
+        match self {
+            MonitorSelector::Name(name) => monitors.iter().find(|monitor| monitor.name == *name),
+            MonitorSelector::Index(index) => monitors.get(*index),
+            MonitorSelector::Primary => monitors.iter().find(|monitor| monitor.primary),
+        }
+    }
+}
+
+impl FromStr for MonitorSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.is_empty() {
+            anyhow::bail!("The monitor name cannot be empty");
+        }
+        if value == "primary" {
+            return Ok(MonitorSelector::Primary);
+        }
+        Ok(match value.parse() {
+            Ok(index) => MonitorSelector::Index(index),
+            Err(_) => MonitorSelector::Name(value.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for MonitorSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorSelector::Name(name) => write!(f, "{:?}", name),
+            MonitorSelector::Index(index) => write!(f, "#{}", index),
+            MonitorSelector::Primary => write!(f, "primary"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::monitor_selector::MonitorSelector;
+    use crate::results::MonitorInfo;
+
+    #[test]
+    fn it_should_parse_monitor_selectors() {
+        assert_eq!("primary".parse::<MonitorSelector>().unwrap(), MonitorSelector::Primary);
+        assert_eq!("1".parse::<MonitorSelector>().unwrap(), MonitorSelector::Index(1));
+        assert_eq!("DP-1".parse::<MonitorSelector>().unwrap(), MonitorSelector::Name("DP-1".to_string()));
+        assert!("".parse::<MonitorSelector>().is_err());
+    }
+
+    #[test]
+    fn it_should_select_monitors() {
+        // Given
+        let monitors = vec![monitor("HDMI-1", false), monitor("DP-1", true), monitor("DP-2", false)];
+
+        // Then
+        assert_eq!(MonitorSelector::Name("DP-2".to_string()).select(&monitors), Some(&monitors[2]));
+        assert_eq!(MonitorSelector::Index(0).select(&monitors), Some(&monitors[0]));
+        assert_eq!(MonitorSelector::Primary.select(&monitors), Some(&monitors[1]));
+        assert_eq!(MonitorSelector::Name("eDP-1".to_string()).select(&monitors), None);
+        assert_eq!(MonitorSelector::Index(3).select(&monitors), None);
+    }
+
+    fn monitor(name: &str, primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            name: name.to_string(),
+            primary,
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::gateways::PresenterGateway;
+use crate::results::ResultType;
+
+pub struct FakePresenterAdapter {
+    /// Shared, so the countdown can still be inspected once the adapter is given to a use case.
+    pub countdowns: Rc<RefCell<Vec<u64>>>,
+}
+
+impl FakePresenterAdapter {
+    pub fn new() -> Self {
+        Self {
+            countdowns: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+}
+
+impl Default for FakePresenterAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresenterGateway for FakePresenterAdapter {
+    fn present_error(&self, _cause: String) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn present_result(&self, _result: &ResultType) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn present_countdown(&self, remaining_seconds: u64) -> anyhow::Result<()> {
+        self.countdowns.borrow_mut().push(remaining_seconds);
+        Ok(())
+    }
+}
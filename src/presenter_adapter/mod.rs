@@ -1,2 +1,3 @@
 pub mod plain_text_presenter_adapter;
-pub mod serde_presenter_adapter;
\ No newline at end of file
+pub mod serde_presenter_adapter;
+pub mod fake_presenter_adapter;
\ No newline at end of file
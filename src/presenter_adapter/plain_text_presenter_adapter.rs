@@ -9,6 +9,12 @@ impl PlainTextPresenterAdapter {
     }
 }
 
+impl Default for PlainTextPresenterAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PresenterGateway for PlainTextPresenterAdapter {
     fn present_error(&self, cause: String) -> anyhow::Result<()> {
         println!("Error: {}", cause);
@@ -20,13 +26,64 @@ impl PresenterGateway for PlainTextPresenterAdapter {
             ResultType::ListWindowResult(windows) => {
                 println!("Windows:");
                 for window in windows {
-                    println!("{}", window);
+                    println!(
+                        "{:#010x}  {:>6}  {}.{}  {}",
+                        window.id,
+                        window.pid.map_or("N/A".to_string(), |pid| pid.to_string()),
+                        window.instance.as_deref().unwrap_or("N/A"),
+                        window.class.as_deref().unwrap_or("N/A"),
+                        window.title.as_deref().unwrap_or_default(),
+                    );
+                }
+            },
+            ResultType::ListMonitorsResult(monitors) => {
+                println!("Monitors:");
+                for (index, monitor) in monitors.iter().enumerate() {
+                    println!(
+                        "{}: {} {}x{}+{}+{}{}",
+                        index,
+                        monitor.name,
+                        monitor.width,
+                        monitor.height,
+                        monitor.x,
+                        monitor.y,
+                        if monitor.primary { " (primary)" } else { "" },
+                    );
                 }
             },
             ResultType::TakeScreenShotResult(()) => {
                 println!("Screenshot taken");
             }
+            ResultType::CaptureAllResult(captures) => {
+                println!("Screenshots:");
+                for capture in captures {
+                    println!(
+                        "{:#010x}  {}  {}",
+                        capture.window.id,
+                        capture.window.title.as_deref().unwrap_or_default(),
+                        match &capture.result {
+                            Ok(path) => path.clone(),
+                            Err(cause) => format!("Error: {}", cause),
+                        },
+                    );
+                }
+            }
+            ResultType::TimelapseResult(paths) => {
+                println!("Screenshots taken:");
+                for path in paths {
+                    println!("{}", path);
+                }
+            }
+            ResultType::RecordResult(frame_count) => {
+                println!("Recording saved ({} frames)", frame_count);
+            }
         }
         Ok(())
     }
+
+    // On the error output, so that it does not mix with the result
+    fn present_countdown(&self, remaining_seconds: u64) -> anyhow::Result<()> {
+        eprintln!("Taking the screenshot in {}...", remaining_seconds);
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -10,6 +10,12 @@ impl SerdePresenterAdapter {
     }
 }
 
+impl Default for SerdePresenterAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PresenterGateway for SerdePresenterAdapter {
     fn present_error(&self, cause: String) -> anyhow::Result<()> {
         let res = ErrorResult { _type: "ErrorResult".to_string(), cause };
@@ -20,7 +26,30 @@ impl PresenterGateway for SerdePresenterAdapter {
     fn present_result(&self, result: &ResultType) -> anyhow::Result<()> {
         match result {
             ResultType::ListWindowResult(windows) => {
-                let res = ListWindowsResult { _type: "ListWindowsResult".to_string(), windows: windows.to_vec() };
+                let res = ListWindowsResult {
+                    _type: "ListWindowsResult".to_string(),
+                    windows: windows.iter().map(|window| Window {
+                        id: window.id,
+                        title: window.title.clone(),
+                        instance: window.instance.clone(),
+                        class: window.class.clone(),
+                        pid: window.pid,
+                    }).collect(),
+                };
+                println!("{}", serde_json::to_string_pretty(&res)?);
+            }
+            ResultType::ListMonitorsResult(monitors) => {
+                let res = ListMonitorsResult {
+                    _type: "ListMonitorsResult".to_string(),
+                    monitors: monitors.iter().map(|monitor| Monitor {
+                        name: monitor.name.clone(),
+                        primary: monitor.primary,
+                        x: monitor.x,
+                        y: monitor.y,
+                        width: monitor.width,
+                        height: monitor.height,
+                    }).collect(),
+                };
                 println!("{}", serde_json::to_string_pretty(&res)?);
             }
             ResultType::TakeScreenShotResult(()) => {
@@ -30,9 +59,41 @@ impl PresenterGateway for SerdePresenterAdapter {
                 };
                 println!("{}", serde_json::to_string_pretty(&res)?);
             }
+            ResultType::CaptureAllResult(captures) => {
+                let res = CaptureAllResult {
+                    _type: "CaptureAllResult".to_string(),
+                    captures: captures.iter().map(|capture| WindowCapture {
+                        id: capture.window.id,
+                        title: capture.window.title.clone(),
+                        file: capture.result.as_ref().ok().cloned(),
+                        cause: capture.result.as_ref().err().cloned(),
+                    }).collect(),
+                };
+                println!("{}", serde_json::to_string_pretty(&res)?);
+            }
+            ResultType::TimelapseResult(paths) => {
+                let res = TimelapseResult {
+                    _type: "TimelapseResult".to_string(),
+                    files: paths.clone(),
+                };
+                println!("{}", serde_json::to_string_pretty(&res)?);
+            }
+            ResultType::RecordResult(frame_count) => {
+                let res = RecordResult {
+                    _type: "RecordResult".to_string(),
+                    frames: *frame_count,
+                };
+                println!("{}", serde_json::to_string_pretty(&res)?);
+            }
         }
         Ok(())
     }
+
+    fn present_countdown(&self, remaining_seconds: u64) -> anyhow::Result<()> {
+        let res = CountdownMessage { _type: "CountdownMessage".to_string(), remaining_seconds };
+        eprintln!("{}", serde_json::to_string(&res)?);
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -44,11 +105,68 @@ struct ErrorResult {
 #[derive(Serialize, Deserialize)]
 struct ListWindowsResult {
     _type: String,
-    windows: Vec<String>,
+    windows: Vec<Window>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Window {
+    id: u64,
+    title: Option<String>,
+    instance: Option<String>,
+    class: Option<String>,
+    pid: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ListMonitorsResult {
+    _type: String,
+    monitors: Vec<Monitor>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Monitor {
+    name: String,
+    primary: bool,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
 }
 
 #[derive(Serialize, Deserialize)]
 struct GenericSuccessMessage {
     _type: String,
     message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CaptureAllResult {
+    _type: String,
+    captures: Vec<WindowCapture>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WindowCapture {
+    id: u64,
+    title: Option<String>,
+    file: Option<String>,
+    cause: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimelapseResult {
+    _type: String,
+    files: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordResult {
+    _type: String,
+    frames: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CountdownMessage {
+    _type: String,
+    remaining_seconds: u64,
 }
\ No newline at end of file
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::animation::AnimationRecorder;
+use crate::results::ResultType;
+use crate::stop_request::StopRequest;
+use crate::take_screen_shot_usecase::{composite_over_black, CaptureOptions, TakeScreenShotUseCase};
+use crate::window_selector::CaptureTarget;
+
+// How often the stop request is checked while waiting for the next frame
+const STOP_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Captures the target at a given frame rate into an animation, for a given duration or until a stop is requested
+/// (e.g. with Ctrl-C). The frames are displayed as long as they actually were on the screen, so a slow capture
+/// does not speed up the animation.
+pub struct RecordUseCase {
+    take_screen_shot_usecase: TakeScreenShotUseCase,
+    stop_request: Arc<StopRequest>,
+}
+
+impl RecordUseCase {
+    pub fn new(take_screen_shot_usecase: TakeScreenShotUseCase, stop_request: Arc<StopRequest>) -> Self {
+        Self {
+            take_screen_shot_usecase,
+            stop_request,
+        }
+    }
+
+    /// The target is resolved once, before the first frame. A stop is only accepted from then on.
+    pub fn execute(
+        &mut self,
+        target: CaptureTarget,
+        options: CaptureOptions,
+        fps: u32,
+        duration: Duration,
+        output_template: &str,
+    ) -> anyhow::Result<ResultType> {
+        let usecase = &self.take_screen_shot_usecase;
+        let resolved_target = usecase.resolve_target(&target, &options)?;
+        self.stop_request.accept();
+        let frame_interval = Duration::from_secs(1) / fps;
+        let start = usecase.clock_gateway.now();
+        let mut end = start + duration;
+        let mut recorder = AnimationRecorder::new();
+        for index in 1.. {
+            let captured_at = usecase.clock_gateway.now();
+            let mut image_buffer = usecase.capture(&resolved_target, &options)?;
+            composite_over_black(&mut image_buffer);
+            recorder.push(captured_at, image_buffer);
+
+            let next_frame = start + frame_interval * index;
+            if next_frame >= end {
+                break;
+            }
+            if !self.wait_until(next_frame) {
+                end = usecase.clock_gateway.now();
+                break;
+            }
+        }
+        let frames = recorder.into_frames(end);
+        let frame_count = frames.len();
+        let output_path = usecase.resolve_output_path(output_template, resolved_target.client_window_id)?;
+        self.take_screen_shot_usecase.fs_gateway.save_animation(frames, &output_path)?;
+        Ok(ResultType::RecordResult(frame_count))
+    }
+
+    /// Returns false if a stop has been requested meanwhile.
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let clock_gateway = &self.take_screen_shot_usecase.clock_gateway;
+        loop {
+            if self.stop_request.is_requested() {
+                return false;
+            }
+            let now = clock_gateway.now();
+            if now >= deadline {
+                return true;
+            }
+            clock_gateway.sleep((deadline - now).min(STOP_POLLING_INTERVAL));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::clock::fake_clock_adapter::FakeClockAdapter;
+    use crate::fs::fake_file_system_adapter::FakeFileSystemAdapter;
+    use crate::record_usecase::RecordUseCase;
+    use crate::results::ResultType;
+    use crate::stop_request::StopRequest;
+    use crate::take_screen_shot_usecase::tests::title_target;
+    use crate::take_screen_shot_usecase::{CaptureOptions, TakeScreenShotUseCase};
+    use crate::window_system::fake_window_system_adapter::FakeWindowSystemAdapter;
+
+    #[test]
+    fn it_should_record_frames_at_the_requested_rate() {
+        // Given
+        let captures = Cell::new(0u8);
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(move || {
+                captures.set(captures.get() + 1);
+                Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([captures.get(), 0, 0, 255])))
+            })
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let animations = fs_gateway.animations.clone();
+        let mut usecase = RecordUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(matches!(result, Ok(ResultType::RecordResult(10))));
+        let animations = animations.borrow();
+        let (frames, path) = &animations[0];
+        assert_eq!(path, "record.gif");
+        assert_eq!(frames.len(), 10);
+        assert!(frames.iter().all(|frame| frame.delay == Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn it_should_merge_the_frames_while_the_content_does_not_change() {
+        // Given
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let animations = fs_gateway.animations.clone();
+        let mut usecase = RecordUseCase::new(
+            TakeScreenShotUseCase::new(
+                Box::new(FakeWindowSystemAdapter::capturing_window(1)),
+                Box::new(fs_gateway),
+                Box::new(FakeClockAdapter::new()),
+            ),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(matches!(result, Ok(ResultType::RecordResult(1))));
+        assert_eq!(animations.borrow()[0].0[0].delay, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn it_should_composite_the_translucent_pixels_over_black() {
+        // Given
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([200, 100, 50, 128]))))
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let animations = fs_gateway.animations.clone();
+        let mut usecase = RecordUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(animations.borrow()[0].0[0].image.get_pixel(0, 0), &image::Rgba([100, 50, 25, 255]));
+    }
+
+    #[test]
+    fn it_should_save_the_frames_recorded_until_a_stop_is_requested() {
+        // Given
+        let stop_request = Arc::new(StopRequest::new());
+        stop_request.accept();
+        stop_request.request();
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let animations = fs_gateway.animations.clone();
+        let mut usecase = RecordUseCase::new(
+            TakeScreenShotUseCase::new(
+                Box::new(FakeWindowSystemAdapter::capturing_window(1)),
+                Box::new(fs_gateway),
+                Box::new(FakeClockAdapter::new()),
+            ),
+            stop_request.clone(),
+        );
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(matches!(result, Ok(ResultType::RecordResult(1))));
+        assert_eq!(animations.borrow()[0].0[0].delay, Duration::ZERO);
+        assert!(stop_request.is_requested());
+    }
+
+    #[test]
+    fn it_should_refuse_a_stop_requested_before_the_recording_starts() {
+        // Given
+        let stop_request = Arc::new(StopRequest::new());
+        let finder_stop_request = stop_request.clone();
+        let stop_accepted = Rc::new(Cell::new(None));
+        let finder_stop_accepted = stop_accepted.clone();
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(move |_| {
+                finder_stop_accepted.set(Some(finder_stop_request.request()));
+                Ok(Some(1))
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)))
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = RecordUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            stop_request,
+        );
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(matches!(result, Ok(ResultType::RecordResult(1))));
+        assert_eq!(stop_accepted.get(), Some(false));
+    }
+
+    #[test]
+    fn it_should_report_saving_failures() {
+        // Given
+        let mut usecase = RecordUseCase::new(
+            TakeScreenShotUseCase::new(
+                Box::new(FakeWindowSystemAdapter::capturing_window(1)),
+                Box::new(FakeFileSystemAdapter::new()),
+                Box::new(FakeClockAdapter::new()),
+            ),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(result.err().unwrap().to_string(), "Unable to save file");
+    }
+
+    fn when(usecase: &mut RecordUseCase) -> anyhow::Result<ResultType> {
+        usecase.execute(
+            title_target(),
+            CaptureOptions::default(),
+            10,
+            Duration::from_secs(1),
+            "record.gif",
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A rectangle within a drawable, parsed from the X geometry syntax `WIDTHxHEIGHT+X+Y`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Shrinks the region so that it fits within a drawable of the given size.
+    pub fn clamp_to(&self, width: u32, height: u32) -> anyhow::Result<Region> {
+        if self.x >= width || self.y >= height {
+            anyhow::bail!(
+                "The region {} starts outside of the {}x{} drawable",
+                self, width, height,
+            );
+        }
+        Ok(Region {
+            x: self.x,
+            y: self.y,
+            width: self.width.min(width - self.x),
+            height: self.height.min(height - self.y),
+        })
+    }
+}
+
+impl FromStr for Region {
+    type Err = anyhow::Error;
+
+    fn from_str(geometry: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow::anyhow!(
+            "Invalid geometry {:?}, expected WIDTHxHEIGHT+X+Y (e.g. 640x480+10+20)", geometry
+        );
+        let (size, offsets) = match geometry.find('+') {
+            Some(index) => geometry.split_at(index),
+            None => (geometry, ""),
+        };
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        let width: u32 = width.parse().map_err(|_| invalid())?;
+        let height: u32 = height.parse().map_err(|_| invalid())?;
+        if width == 0 || height == 0 {
+            anyhow::bail!("Invalid geometry {:?}, the width and the height must be positive", geometry);
+        }
+
+        let (x, y) = match offsets.strip_prefix('+') {
+            None => (0, 0),
+            Some(offsets) => {
+                let (x, y) = offsets.split_once('+').ok_or_else(invalid)?;
+                (x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?)
+            }
+        };
+        Ok(Region { x, y, width, height })
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::region::Region;
+
+    #[test]
+    fn it_should_parse_geometries() {
+        assert_eq!("640x480+10+20".parse::<Region>().unwrap(), Region { x: 10, y: 20, width: 640, height: 480 });
+        assert_eq!("640x480".parse::<Region>().unwrap(), Region { x: 0, y: 0, width: 640, height: 480 });
+    }
+
+    #[test]
+    fn it_should_reject_invalid_geometries() {
+        for geometry in ["", "640", "640x", "x480", "640x480+10", "640x480-10-20", "-640x480", "axb+1+2"] {
+            assert_eq!(
+                geometry.parse::<Region>().unwrap_err().to_string(),
+                format!("Invalid geometry {:?}, expected WIDTHxHEIGHT+X+Y (e.g. 640x480+10+20)", geometry),
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_reject_empty_geometries() {
+        assert_eq!(
+            "0x480+10+20".parse::<Region>().unwrap_err().to_string(),
+            "Invalid geometry \"0x480+10+20\", the width and the height must be positive",
+        );
+    }
+
+    #[test]
+    fn it_should_clamp_the_region_to_the_drawable() {
+        // Given
+        let region = Region { x: 600, y: 400, width: 100, height: 50 };
+
+        // When
+        let clamped = region.clamp_to(640, 480).unwrap();
+
+        // Then
+        assert_eq!(clamped, Region { x: 600, y: 400, width: 40, height: 50 });
+    }
+
+    #[test]
+    fn it_should_keep_regions_that_fit_in_the_drawable() {
+        // Given
+        let region = Region { x: 0, y: 0, width: 640, height: 480 };
+
+        // Then
+        assert_eq!(region.clamp_to(640, 480).unwrap(), region);
+    }
+
+    #[test]
+    fn it_should_reject_regions_starting_outside_of_the_drawable() {
+        // Given
+        let region = Region { x: 640, y: 0, width: 10, height: 10 };
+
+        // When
+        let result = region.clamp_to(640, 480);
+
+        // Then
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The region 10x10+640+0 starts outside of the 640x480 drawable",
+        );
+    }
+}
@@ -1,4 +1,38 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub id: u64,
+    pub title: Option<String>,
+    pub instance: Option<String>,
+    pub class: Option<String>,
+    pub pid: Option<u32>,
+}
+
+/// A monitor (XRandR output), positioned in root window coordinates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub primary: bool,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The outcome of the capture of one of several windows: the path of the saved file, or the cause of the failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowCapture {
+    pub window: WindowInfo,
+    pub result: Result<String, String>,
+}
+
 pub enum ResultType {
-    ListWindowResult(Vec<String>),
+    ListWindowResult(Vec<WindowInfo>),
+    ListMonitorsResult(Vec<MonitorInfo>),
     TakeScreenShotResult(()),
+    /// One entry per matching window, in the order they were found.
+    CaptureAllResult(Vec<WindowCapture>),
+    /// The paths of the saved files, in capture order.
+    TimelapseResult(Vec<String>),
+    /// The number of distinct frames of the saved animation.
+    RecordResult(usize),
 }
\ No newline at end of file
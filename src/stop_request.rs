@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A request to stop a capture loop, shared with the Ctrl-C handler. It is only accepted once the loop has started,
+/// so that the delay, the wait for the window or the pick can still be interrupted as usual beforehand.
+#[derive(Default)]
+pub struct StopRequest {
+    accepted: AtomicBool,
+    requested: AtomicBool,
+}
+
+impl StopRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Called by the capture loop once it has started.
+    pub fn accept(&self) {
+        self.accepted.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns false if the capture loop has not started yet, leaving it to the caller to interrupt the process.
+    pub fn request(&self) -> bool {
+        if !self.accepted.load(Ordering::SeqCst) {
+            return false;
+        }
+        self.requested.store(true, Ordering::SeqCst);
+        true
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stop_request::StopRequest;
+
+    #[test]
+    fn it_should_refuse_a_stop_requested_before_the_loop_has_started() {
+        // Given
+        let stop_request = StopRequest::new();
+
+        // When
+        let accepted = stop_request.request();
+
+        // Then
+        assert!(!accepted);
+        assert!(!stop_request.is_requested());
+    }
+
+    #[test]
+    fn it_should_record_a_stop_requested_once_the_loop_has_started() {
+        // Given
+        let stop_request = StopRequest::new();
+        stop_request.accept();
+
+        // When
+        let accepted = stop_request.request();
+
+        // Then
+        assert!(accepted);
+        assert!(stop_request.is_requested());
+    }
+}
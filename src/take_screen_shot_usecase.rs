@@ -1,50 +1,388 @@
-use crate::gateways::{FileSystemGateway, ScreenShotWindowSystemGateway};
-use crate::results::ResultType;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use image::DynamicImage;
+
+use crate::file_name_template::{distinct_per_window, needs_window, render, with_date, with_window};
+use crate::gateways::{ClockGateway, FileSystemGateway, PresenterGateway, ScreenShotWindowSystemGateway};
+use crate::monitor_selector::MonitorSelector;
+use crate::region::Region;
+use crate::results::{ResultType, WindowCapture};
+use crate::window_selector::{CaptureTarget, WindowSelector};
+
+const POLLING_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Default)]
+pub struct CaptureOptions {
+    /// Only captures this part of the target, relatively to its top left corner.
+    pub region: Option<Region>,
+    /// Captures the frame drawn around the window by the window manager (title bar, borders).
+    pub with_frame: bool,
+    /// Keeps the alpha channel of ARGB windows instead of saving opaque pixels.
+    pub keep_alpha: bool,
+    /// Draws the mouse cursor onto the capture.
+    pub cursor: bool,
+    /// Waits before looking for the target, e.g. to give the time to open a menu.
+    pub delay: Option<Duration>,
+    /// Keeps looking for the searched window until one of the matching windows is mapped, for at most this long.
+    pub wait_timeout: Option<Duration>,
+    /// Captures repeatedly until the content of the target stops changing.
+    pub settle: Option<SettleOptions>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettleOptions {
+    /// Number of consecutive similar captures required.
+    pub frames: u32,
+    /// Maximum fraction (between 0 and 1) of pixels which may differ between two similar captures.
+    pub threshold: f64,
+    /// Fails if the content is still changing after this long.
+    pub timeout: Duration,
+}
+
+/// The window designated by a capture target, and the part of it to capture (in window coordinates).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResolvedTarget {
+    pub window_id: u64,
+    /// The window designated by the target, `window_id` being its frame when capturing it.
+    pub client_window_id: u64,
+    pub region: Option<Region>,
+}
 
 pub struct TakeScreenShotUseCase {
     pub window_system_gateway: Box<dyn ScreenShotWindowSystemGateway>,
     pub fs_gateway: Box<dyn FileSystemGateway>,
+    pub clock_gateway: Box<dyn ClockGateway>,
+    countdown_presenter_gateway: Option<Box<dyn PresenterGateway>>,
 }
 
 impl TakeScreenShotUseCase {
     pub fn new(
         window_system_gateway: Box<dyn ScreenShotWindowSystemGateway>,
         fs_gateway: Box<dyn FileSystemGateway>,
+        clock_gateway: Box<dyn ClockGateway>,
     ) -> TakeScreenShotUseCase {
         TakeScreenShotUseCase {
             window_system_gateway,
             fs_gateway,
+            clock_gateway,
+            countdown_presenter_gateway: None,
         }
     }
+
+    /// Presents the remaining seconds of the delay, every second.
+    pub fn with_countdown(mut self, presenter_gateway: Box<dyn PresenterGateway>) -> Self {
+        self.countdown_presenter_gateway = Some(presenter_gateway);
+        self
+    }
+
     pub fn take_screenshot(&mut self,
-                           searched_window_name: String,
+                           target: CaptureTarget,
+                           options: CaptureOptions,
                            output_path: String,
     ) -> anyhow::Result<ResultType> {
-        let Some(target_window) = self.window_system_gateway.find_window(&searched_window_name)? else {
-            anyhow::bail!("Unable to find the window with title {:?}", searched_window_name);
+        self.take_screenshot_to_file(target, options, &output_path)?;
+        Ok(ResultType::TakeScreenShotResult(()))
+    }
+
+    /// Returns the path of the saved file, once its placeholders have been filled in.
+    pub fn take_screenshot_to_file(
+        &mut self,
+        target: CaptureTarget,
+        options: CaptureOptions,
+        output_template: &str,
+    ) -> anyhow::Result<String> {
+        if let Some(delay) = options.delay {
+            self.wait(delay)?;
+        }
+        let resolved_target = self.resolve_target(&target, &options)?;
+        let image_buffer = self.capture(&resolved_target, &options)?;
+        let output_path = self.resolve_output_path(output_template, resolved_target.client_window_id)?;
+        self.save(image_buffer, &options, &output_path)?;
+        Ok(output_path)
+    }
+
+    /// Captures every mapped window matching the selector into its own file, named after the template (see
+    /// `distinct_per_window`). A failure to capture one of them does not prevent capturing the others.
+    pub fn take_screenshots_of_all_windows(
+        &mut self,
+        selector: WindowSelector,
+        options: CaptureOptions,
+        output_template: &str,
+    ) -> anyhow::Result<ResultType> {
+        if let Some(delay) = options.delay {
+            self.wait(delay)?;
+        }
+        if let Some(timeout) = options.wait_timeout {
+            self.wait_for_window(&selector, timeout)?;
+        }
+        let output_template = distinct_per_window(output_template);
+        let mut captures = Vec::new();
+        let mut captured_window_ids = HashSet::new();
+        for window in self.window_system_gateway.find_windows(&selector)? {
+            let window_id = match self.window_system_gateway.is_window_mapped(window.id) {
+                Ok(false) => continue,
+                Ok(true) => self.resolve_window_to_capture(window.id, options.with_frame),
+                Err(error) => Err(error),
+            };
+            // Several matching windows can resolve to the same frame, which is only captured once
+            if window_id.as_ref().is_ok_and(|window_id| !captured_window_ids.insert(*window_id)) {
+                continue;
+            }
+            let result = window_id.and_then(|window_id| with_window(&output_template, &window)
+                .and_then(|output_template| self.save_window_screen_shot(window_id, window.id, &options, &output_template)));
+            captures.push(WindowCapture { window, result: result.map_err(|error| error.to_string()) });
+        }
+        if captures.is_empty() {
+            anyhow::bail!("Unable to find the window with {}", selector);
+        }
+        Ok(ResultType::CaptureAllResult(captures))
+    }
+
+    /// Finds the window to capture and the part of it, which stay the same for consecutive captures of the target.
+    pub fn resolve_target(&self, target: &CaptureTarget, options: &CaptureOptions) -> anyhow::Result<ResolvedTarget> {
+        let client_window_id = self.resolve_target_window(target, options.wait_timeout)?;
+        let window_id = self.resolve_window_to_capture(client_window_id, options.with_frame)?;
+        let region = match target {
+            CaptureTarget::Monitor(selector) => Some(self.resolve_monitor_region(selector, options.region.as_ref())?),
+            _ => options.region,
+        };
+        Ok(ResolvedTarget { window_id, client_window_id, region })
+    }
+
+    /// Captures the resolved target as is, with the cursor drawn onto it if requested.
+    pub fn capture(&self, target: &ResolvedTarget, options: &CaptureOptions) -> anyhow::Result<image::RgbaImage> {
+        let region = target.region.as_ref();
+        let mut image_buffer = match &options.settle {
+            Some(settle) => self.take_settled_screen_shot(target.window_id, region, settle)?,
+            None => self.window_system_gateway.take_screen_shot(target.window_id, region)?,
+        };
+        if options.cursor {
+            let cursor = self.window_system_gateway.get_cursor(target.window_id)?;
+            let (origin_x, origin_y) = region.map_or((0, 0), |region| (region.x, region.y));
+            cursor.draw_onto(&mut image_buffer, origin_x, origin_y);
+        }
+        Ok(image_buffer)
+    }
+
+    /// Saves a capture, without its alpha channel unless requested.
+    pub fn save(
+        &mut self,
+        mut image_buffer: image::RgbaImage,
+        options: &CaptureOptions,
+        output_path: &str,
+    ) -> anyhow::Result<()> {
+        let image_buffer = if options.keep_alpha {
+            DynamicImage::ImageRgba8(image_buffer)
+        } else {
+            composite_over_black(&mut image_buffer);
+            DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(image_buffer).into_rgb8())
+        };
+        self.fs_gateway.save_image(image_buffer, output_path)
+    }
+
+    /// Fills in the date (the current one) and window placeholders of the output file name, the other ones having
+    /// to be filled in beforehand.
+    pub fn resolve_output_path(&self, output_template: &str, window_id: u64) -> anyhow::Result<String> {
+        let mut output_path = with_date(output_template, &self.clock_gateway.date_time())?;
+        if needs_window(&output_path) {
+            output_path = with_window(&output_path, &self.window_system_gateway.get_window_info(window_id)?)?;
+        }
+        render(&output_path)
+    }
+
+    fn resolve_window_to_capture(&self, client_window_id: u64, with_frame: bool) -> anyhow::Result<u64> {
+        if with_frame {
+            self.window_system_gateway.get_frame_window(client_window_id)
+        } else {
+            Ok(client_window_id)
+        }
+    }
+
+    fn save_window_screen_shot(
+        &mut self,
+        window_id: u64,
+        client_window_id: u64,
+        options: &CaptureOptions,
+        output_template: &str,
+    ) -> anyhow::Result<String> {
+        let resolved_target = ResolvedTarget { window_id, client_window_id, region: options.region };
+        let image_buffer = self.capture(&resolved_target, options)?;
+        let output_path = self.resolve_output_path(output_template, client_window_id)?;
+        self.save(image_buffer, options, &output_path)?;
+        Ok(output_path)
+    }
+
+    /// Waits before looking for the target, counting the seconds down if requested.
+    pub fn wait(&self, delay: Duration) -> anyhow::Result<()> {
+        let Some(presenter_gateway) = &self.countdown_presenter_gateway else {
+            self.clock_gateway.sleep(delay);
+            return Ok(());
         };
-        let image_buffer = self.window_system_gateway.take_screen_shot(target_window)?;
-        self.fs_gateway.save_image(image_buffer, &output_path)?;
-        return Ok(ResultType::TakeScreenShotResult(()));
+        let mut remaining = delay;
+        while !remaining.is_zero() {
+            // Sleeps the fraction of second first, so the next steps fall on whole seconds
+            let fraction = Duration::from_nanos(remaining.subsec_nanos() as u64);
+            let step = if fraction.is_zero() { Duration::from_secs(1) } else { fraction };
+            presenter_gateway.present_countdown(remaining.as_secs() + u64::from(!fraction.is_zero()))?;
+            self.clock_gateway.sleep(step);
+            remaining -= step;
+        }
+        Ok(())
+    }
+
+    fn resolve_target_window(&self, target: &CaptureTarget, wait_timeout: Option<Duration>) -> anyhow::Result<u64> {
+        match target {
+            CaptureTarget::Search(selector) => {
+                if let Some(timeout) = wait_timeout {
+                    return self.wait_for_window(selector, timeout);
+                }
+                let Some(target_window) = self.window_system_gateway.find_window(selector)? else {
+                    anyhow::bail!("Unable to find the window with {}", selector);
+                };
+                Ok(target_window)
+            }
+            CaptureTarget::WindowId(window_id) => {
+                if !self.window_system_gateway.window_exists(*window_id)? {
+                    anyhow::bail!("Unable to find the window with id {:#x}", window_id);
+                }
+                Ok(*window_id)
+            }
+            CaptureTarget::Active => {
+                let Some(active_window) = self.window_system_gateway.get_active_window()? else {
+                    anyhow::bail!("Unable to determine the active window");
+                };
+                Ok(active_window)
+            }
+            CaptureTarget::Pick => {
+                let Some(picked_window) = self.window_system_gateway.pick_window()? else {
+                    anyhow::bail!("The window selection was cancelled");
+                };
+                Ok(picked_window)
+            }
+            CaptureTarget::Screen(screen) => self.window_system_gateway.get_root_window(*screen),
+            CaptureTarget::Monitor(_) => self.window_system_gateway.get_root_window(None),
+        }
+    }
+
+    fn wait_for_window(&self, selector: &WindowSelector, timeout: Duration) -> anyhow::Result<u64> {
+        let start = self.clock_gateway.now();
+        loop {
+            if let Some(window) = self.find_mapped_window(selector)? {
+                return Ok(window);
+            }
+            let elapsed = self.clock_gateway.now().duration_since(start);
+            if elapsed >= timeout {
+                anyhow::bail!("Unable to find the window with {} within {:?}", selector, timeout);
+            }
+            self.clock_gateway.sleep(POLLING_INTERVAL.min(timeout - elapsed));
+        }
+    }
+
+    // Toolkits often leave unmapped windows (e.g. client leaders) with the same class or pid as the actual ones
+    fn find_mapped_window(&self, selector: &WindowSelector) -> anyhow::Result<Option<u64>> {
+        for window in self.window_system_gateway.find_windows(selector)? {
+            if self.window_system_gateway.is_window_mapped(window.id)? {
+                return Ok(Some(window.id));
+            }
+        }
+        Ok(None)
+    }
+
+    fn take_settled_screen_shot(
+        &self,
+        window_id: u64,
+        region: Option<&Region>,
+        settle: &SettleOptions,
+    ) -> anyhow::Result<image::RgbaImage> {
+        let start = self.clock_gateway.now();
+        let mut previous = self.window_system_gateway.take_screen_shot(window_id, region)?;
+        let mut similar_frames = 1;
+        while similar_frames < settle.frames {
+            if self.clock_gateway.now().duration_since(start) >= settle.timeout {
+                anyhow::bail!("The content of the window did not settle within {:?}", settle.timeout);
+            }
+            self.clock_gateway.sleep(POLLING_INTERVAL);
+            let current = self.window_system_gateway.take_screen_shot(window_id, region)?;
+            if difference(&previous, &current) <= settle.threshold {
+                similar_frames += 1;
+            } else {
+                similar_frames = 1;
+            }
+            previous = current;
+        }
+        Ok(previous)
+    }
+
+    /// Converts the region relative to the monitor (if any) to a region of the root window.
+    fn resolve_monitor_region(&self, selector: &MonitorSelector, region: Option<&Region>) -> anyhow::Result<Region> {
+        let monitors = self.window_system_gateway.list_monitors()?;
+        let Some(monitor) = selector.select(&monitors) else {
+            anyhow::bail!("Unable to find the monitor {}", selector);
+        };
+        let monitor_region = Region { x: 0, y: 0, width: monitor.width, height: monitor.height };
+        let region = match region {
+            Some(region) => region.clamp_to(monitor.width, monitor.height)?,
+            None => monitor_region,
+        };
+        Ok(Region { x: monitor.x + region.x, y: monitor.y + region.y, ..region })
+    }
+}
+
+/// Fraction of the pixels which differ between both images (all of them if their sizes differ).
+fn difference(first: &image::RgbaImage, second: &image::RgbaImage) -> f64 {
+    if first.dimensions() != second.dimensions() {
+        return 1.0;
+    }
+    let total = first.pixels().len();
+    if total == 0 {
+        return 0.0;
+    }
+    let different = first.pixels().zip(second.pixels()).filter(|(a, b)| a != b).count();
+    different as f64 / total as f64
+}
+
+/// Makes the translucent pixels of ARGB windows (e.g. shadows, rounded corners) opaque, as they would look like over
+/// a black background. These are the premultiplied colors held by the X server, before they got unpremultiplied.
+pub fn composite_over_black(image_buffer: &mut image::RgbaImage) {
+    for pixel in image_buffer.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel.0[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+        pixel[3] = u8::MAX;
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use image::DynamicImage;
+
+    use crate::clock::fake_clock_adapter::FakeClockAdapter;
+    use crate::cursor::Cursor;
     use crate::fs::fake_file_system_adapter::FakeFileSystemAdapter;
-    use crate::results::ResultType;
-    use crate::take_screen_shot_usecase::TakeScreenShotUseCase;
+    use crate::monitor_selector::MonitorSelector;
+    use crate::presenter_adapter::fake_presenter_adapter::FakePresenterAdapter;
+    use crate::region::Region;
+    use crate::results::{MonitorInfo, ResultType, WindowInfo};
+    use crate::take_screen_shot_usecase::{CaptureOptions, SettleOptions, TakeScreenShotUseCase};
+    use crate::window_matcher::{MatchMode, WindowMatcher};
+    use crate::window_selector::{CaptureTarget, WindowSelector};
     use crate::window_system::fake_window_system_adapter::FakeWindowSystemAdapter;
 
+    const WHITE: image::Rgba<u8> = image::Rgba([255, 255, 255, 255]);
+    const BLACK: image::Rgba<u8> = image::Rgba([0, 0, 0, 255]);
+
     #[test]
     fn it_should_report_finding_window_failures() {
         // Given
-        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new());
-        let fs_gateway = Box::new(FakeFileSystemAdapter::new());
-        let mut usecase = TakeScreenShotUseCase::new(
-            window_system_gateway,
-            fs_gateway
-        );
+        let window_system_gateway = FakeWindowSystemAdapter::new();
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
 
         // When
         let result = when(&mut usecase);
@@ -56,13 +394,10 @@ mod tests {
     #[test]
     fn it_should_yield_an_error_if_the_target_window_cannot_be_found() {
         // Given
-        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
-            .with_find_window_result(Box::new(|| Ok(None))));
-        let fs_gateway = Box::new(FakeFileSystemAdapter::new());
-        let mut usecase = TakeScreenShotUseCase::new(
-            window_system_gateway,
-            fs_gateway
-        );
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(None));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
 
         // When
         let result = when(&mut usecase);
@@ -74,14 +409,10 @@ mod tests {
     #[test]
     fn it_should_report_screenshot_taking_failures() {
         // Given
-        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
-            .with_find_window_result(Box::new(|| Ok(Some(1))))
-        );
-        let fs_gateway = Box::new(FakeFileSystemAdapter::new());
-        let mut usecase = TakeScreenShotUseCase::new(
-            window_system_gateway,
-            fs_gateway
-        );
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
 
         // When
         let result = when(&mut usecase);
@@ -93,15 +424,9 @@ mod tests {
     #[test]
     fn it_should_report_image_saving_failures() {
         // Given
-        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
-            .with_find_window_result(Box::new(|| Ok(Some(1))))
-            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbImage::new(1, 1))))
-        );
-        let fs_gateway = Box::new(FakeFileSystemAdapter::new());
-        let mut usecase = TakeScreenShotUseCase::new(
-            window_system_gateway,
-            fs_gateway
-        );
+        let window_system_gateway = FakeWindowSystemAdapter::capturing_window(1);
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
 
         // When
         let result = when(&mut usecase);
@@ -113,27 +438,1039 @@ mod tests {
     #[test]
     fn it_should_work() {
         // Given
-        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
-            .with_find_window_result(Box::new(|| Ok(Some(1))))
-            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbImage::new(1, 1))))
+        let window_system_gateway = FakeWindowSystemAdapter::capturing_window(1);
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_find_the_window_using_the_given_match_mode() {
+        for (searched_window_name, match_mode) in [
+            ("main.rs - editor", MatchMode::Exact),
+            ("editor", MatchMode::Substring),
+            ("MAIN.RS - EDITOR", MatchMode::CaseInsensitive),
+            (r"^main\.rs - \w+$", MatchMode::Regex),
+        ] {
+            // Given
+            let window_system_gateway = FakeWindowSystemAdapter::new()
+                .with_find_window_result(|selector| {
+                    Ok(selector.matches(&window("main.rs - editor", "emacs")).then_some(1))
+                })
+                .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+            let fs_gateway = FakeFileSystemAdapter::saving();
+            let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+            // When
+            let result = when_capturing(
+                &mut usecase,
+                CaptureTarget::Search(WindowSelector::Title(WindowMatcher::new(searched_window_name, match_mode).unwrap())),
+            );
+
+            // Then
+            assert!(result.is_ok(), "{:?} should match with {:?}", searched_window_name, match_mode);
+        }
+    }
+
+    #[test]
+    fn it_should_not_find_the_window_if_the_match_mode_does_not_match() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|selector| {
+                Ok(selector.matches(&window("main.rs - editor*", "emacs")).then_some(1))
+            });
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(
+            &mut usecase,
+            CaptureTarget::Search(WindowSelector::Title(WindowMatcher::new("main.rs - editor", MatchMode::Exact).unwrap())),
         );
-        let fs_gateway = Box::new(FakeFileSystemAdapter::new()
-            .with_result(Box::new(|| Ok(())))
+
+        // Then
+        assert_error(result, "Unable to find the window with title \"main.rs - editor\"");
+    }
+
+    #[test]
+    fn it_should_find_the_window_by_class() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|selector| {
+                Ok(selector.matches(&window("main.rs - editor*", "emacs")).then_some(1))
+            })
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(
+            &mut usecase,
+            CaptureTarget::Search(WindowSelector::Class(WindowMatcher::new("Emacs", MatchMode::CaseInsensitive).unwrap())),
         );
-        let mut usecase = TakeScreenShotUseCase::new(
-            window_system_gateway,
-            fs_gateway
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_no_window_has_the_searched_class() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(None));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(
+            &mut usecase,
+            CaptureTarget::Search(WindowSelector::Class(WindowMatcher::new("xterm", MatchMode::Exact).unwrap())),
+        );
+
+        // Then
+        assert_error(result, "Unable to find the window with class \"xterm\"");
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_no_window_belongs_to_the_searched_process() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(None));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(
+            &mut usecase,
+            CaptureTarget::Search(WindowSelector::Pid { pid: 1234, include_children: true }),
+        );
+
+        // Then
+        assert_error(result, "Unable to find the window with pid 1234 or one of its child processes");
+    }
+
+    #[test]
+    fn it_should_capture_the_window_with_the_given_id_without_searching_it() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_window_exists_result(|window_id| Ok(window_id == 0x3a00007))
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::WindowId(0x3a00007));
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_the_window_with_the_given_id_does_not_exist() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_window_exists_result(|_| Ok(false));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::WindowId(0x3a00007));
+
+        // Then
+        assert_error(result, "Unable to find the window with id 0x3a00007");
+    }
+
+    #[test]
+    fn it_should_capture_the_active_window() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_get_active_window_result(|| Ok(Some(1)))
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Active);
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_no_window_is_active() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_get_active_window_result(|| Ok(None));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Active);
+
+        // Then
+        assert_error(result, "Unable to determine the active window");
+    }
+
+    #[test]
+    fn it_should_capture_the_picked_window() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_pick_window_result(|| Ok(Some(1)))
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Pick);
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_the_window_selection_is_cancelled() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_pick_window_result(|| Ok(None));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Pick);
+
+        // Then
+        assert_error(result, "The window selection was cancelled");
+    }
+
+    #[test]
+    fn it_should_report_window_picking_failures() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new();
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Pick);
+
+        // Then
+        assert_error(result, "Unable to pick a window.");
+    }
+
+    #[test]
+    fn it_should_capture_the_whole_screen_without_looking_for_a_window() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_get_root_window_result(|screen| {
+                assert_eq!(screen, Some(1));
+                Ok(1)
+            })
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Screen(Some(1)));
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_report_root_window_failures() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new();
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Screen(Some(3)));
+
+        // Then
+        assert_error(result, "Unable to get the root window.");
+    }
+
+    #[test]
+    fn it_should_capture_the_monitor_area_of_the_root_window() {
+        // Given
+        let captured_region = Rc::new(RefCell::new(None));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_list_monitors_result(|| Ok(vec![monitor("DP-1", 0, false), monitor("DP-2", 1920, true)]))
+            .with_get_root_window_result(|_| Ok(1))
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))))
+            .with_take_screen_shot_region_spy(captured_region.clone());
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Monitor(MonitorSelector::Primary));
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*captured_region.borrow(), Some(Region { x: 1920, y: 0, width: 1920, height: 1080 }));
+    }
+
+    #[test]
+    fn it_should_capture_a_region_relative_to_the_monitor() {
+        // Given
+        let captured_region = Rc::new(RefCell::new(None));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_list_monitors_result(|| Ok(vec![monitor("DP-1", 0, true), monitor("DP-2", 1920, false)]))
+            .with_get_root_window_result(|_| Ok(1))
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))))
+            .with_take_screen_shot_region_spy(captured_region.clone());
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            region: Some(Region { x: 1800, y: 10, width: 200, height: 100 }),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = usecase.take_screenshot(
+            CaptureTarget::Monitor(MonitorSelector::Name("DP-2".to_string())),
+            options,
+            "output_path".to_string(),
+        );
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*captured_region.borrow(), Some(Region { x: 3720, y: 10, width: 120, height: 100 }));
+    }
+
+    #[test]
+    fn it_should_capture_the_given_region_of_the_window() {
+        // Given
+        let captured_region = Rc::new(RefCell::new(None));
+        let window_system_gateway = FakeWindowSystemAdapter::capturing_window(1)
+            .with_take_screen_shot_region_spy(captured_region.clone());
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            region: Some(Region { x: 10, y: 20, width: 640, height: 480 }),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*captured_region.borrow(), Some(Region { x: 10, y: 20, width: 640, height: 480 }));
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_the_region_starts_outside_of_the_monitor() {
+        // Given
+        let captured_region = Rc::new(RefCell::new(None));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_list_monitors_result(|| Ok(vec![monitor("DP-1", 0, true)]))
+            .with_get_root_window_result(|_| Ok(1))
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))))
+            .with_take_screen_shot_region_spy(captured_region.clone());
+        let mut usecase = usecase_with(window_system_gateway, FakeFileSystemAdapter::new());
+        let options = CaptureOptions {
+            region: Some(Region { x: 1920, y: 0, width: 100, height: 100 }),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = usecase.take_screenshot(
+            CaptureTarget::Monitor(MonitorSelector::Primary),
+            options,
+            "output_path".to_string(),
+        );
+
+        // Then
+        assert_error(result, "The region 100x100+1920+0 starts outside of the 1920x1080 drawable");
+        assert_eq!(*captured_region.borrow(), None);
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_the_monitor_cannot_be_found() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_list_monitors_result(|| Ok(vec![monitor("DP-1", 0, true)]))
+            .with_get_root_window_result(|_| Ok(1));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing(&mut usecase, CaptureTarget::Monitor(MonitorSelector::Index(1)));
+
+        // Then
+        assert_error(result, "Unable to find the monitor #1");
+    }
+
+    #[test]
+    fn it_should_capture_the_frame_of_the_window() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_get_frame_window_result(|window_id| {
+                assert_eq!(window_id, 1);
+                Ok(2)
+            })
+            .with_take_screen_shot_result(Box::new(|| Ok(image::RgbaImage::new(1, 1))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            with_frame: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_report_frame_window_failures() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            with_frame: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert_error(result, "Unable to get the frame window.");
+    }
+
+    #[test]
+    fn it_should_save_opaque_images_composited_over_black_by_default() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([200, 100, 50, 128]))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when(&mut usecase);
+
+        // Then
+        assert!(result.is_ok());
+        let saved_images = saved_images.borrow();
+        assert_eq!(saved_images[0].0, DynamicImage::ImageRgb8(image::RgbImage::from_pixel(1, 1, image::Rgb([100, 50, 25]))));
+        assert_eq!(saved_images[0].1, "output_path");
+    }
+
+    #[test]
+    fn it_should_keep_the_alpha_channel_if_requested() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([10, 20, 30, 128]))));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            keep_alpha: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(
+            saved_images.borrow()[0].0,
+            DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, image::Rgba([10, 20, 30, 128]))),
+        );
+    }
+
+    #[test]
+    fn it_should_fill_in_the_output_file_name_with_the_date_and_the_captured_window() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_get_frame_window_result(|_| Ok(2))
+            .with_get_window_info_result(|window_id| Ok(WindowInfo { id: window_id, ..window("~/src: vim", "XTerm") }))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            with_frame: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = usecase.take_screenshot(
+            title_target(),
+            options,
+            "{date}-{class}-{title}-{window_id}.png".to_string(),
+        );
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(saved_images.borrow()[0].1, "20240501-123456-XTerm-~_src: vim-0x1.png");
+    }
+
+    #[test]
+    fn it_should_report_invalid_output_file_names() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let mut usecase = usecase_with(window_system_gateway, FakeFileSystemAdapter::new());
+
+        // When
+        let result = usecase.take_screenshot(
+            title_target(),
+            CaptureOptions::default(),
+            "{seq}.png".to_string(),
+        );
+
+        // Then
+        assert_error(result, "The {seq} placeholder can only be used when taking several screenshots");
+    }
+
+    #[test]
+    fn it_should_draw_the_cursor_relatively_to_the_captured_region_if_requested() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::from_pixel(3, 3, WHITE)))
+            .with_get_cursor_result(|window_id| {
+                assert_eq!(window_id, 1);
+                Ok(Cursor { x: 11, y: 22, image: image::RgbaImage::from_pixel(1, 1, BLACK) })
+            });
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            region: Some(Region { x: 10, y: 20, width: 3, height: 3 }),
+            cursor: true,
+            keep_alpha: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        let mut expected = image::RgbaImage::from_pixel(3, 3, WHITE);
+        expected.put_pixel(1, 2, BLACK);
+        assert_eq!(saved_images.borrow()[0].0, DynamicImage::ImageRgba8(expected));
+    }
+
+    #[test]
+    fn it_should_report_cursor_failures() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            cursor: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert_error(result, "Unable to get the cursor.");
+    }
+
+    #[test]
+    fn it_should_wait_for_the_delay_before_looking_for_the_window() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let sleeps_when_searching = sleeps.clone();
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(move |_| {
+                assert_eq!(*sleeps_when_searching.borrow(), vec![Duration::from_secs(5)]);
+                Ok(Some(1))
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            delay: Some(Duration::from_secs(5)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*sleeps.borrow(), vec![Duration::from_secs(5)]);
+    }
+
+    #[test]
+    fn it_should_count_the_remaining_seconds_down_if_requested() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let presenter_gateway = FakePresenterAdapter::new();
+        let countdowns = presenter_gateway.countdowns.clone();
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway)
+            .with_countdown(Box::new(presenter_gateway));
+        let options = CaptureOptions {
+            delay: Some(Duration::from_millis(2500)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*countdowns.borrow(), vec![3, 2, 1]);
+        assert_eq!(
+            *sleeps.borrow(),
+            vec![Duration::from_millis(500), Duration::from_secs(1), Duration::from_secs(1)],
         );
+    }
+
+    #[test]
+    fn it_should_not_wait_without_delay() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
 
         // When
         let result = when(&mut usecase);
 
         // Then
         assert!(result.is_ok());
+        assert!(sleeps.borrow().is_empty());
+    }
+
+    #[test]
+    fn it_should_wait_for_the_window_to_appear_if_requested() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let searches = Rc::new(Cell::new(0));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(move |_| {
+                searches.set(searches.get() + 1);
+                Ok(if searches.get() >= 3 { vec![window("window_name", "XTerm")] } else { Vec::new() })
+            })
+            .with_is_window_mapped_result(|_| Ok(true))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            wait_timeout: Some(Duration::from_secs(10)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*sleeps.borrow(), vec![Duration::from_millis(100), Duration::from_millis(100)]);
+    }
+
+    #[test]
+    fn it_should_wait_for_the_found_window_to_be_mapped() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let checks = Rc::new(Cell::new(0));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(vec![window("window_name", "XTerm")]))
+            .with_is_window_mapped_result(move |window_id| {
+                assert_eq!(window_id, 1);
+                checks.set(checks.get() + 1);
+                Ok(checks.get() >= 2)
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            wait_timeout: Some(Duration::from_secs(10)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(*sleeps.borrow(), vec![Duration::from_millis(100)]);
+    }
+
+    #[test]
+    fn it_should_wait_for_any_matching_window_to_be_mapped() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let captured_window = Rc::new(Cell::new(None));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(vec![
+                WindowInfo { id: 1, ..window("window_name", "XTerm") },
+                WindowInfo { id: 2, ..window("window_name", "XTerm") },
+            ]))
+            .with_is_window_mapped_result(|window_id| Ok(window_id == 2))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)))
+            .with_take_screen_shot_window_spy(captured_window.clone());
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            wait_timeout: Some(Duration::from_secs(10)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(captured_window.get(), Some(2));
+        assert!(sleeps.borrow().is_empty());
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_the_window_does_not_appear_in_time() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(Vec::new()));
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            wait_timeout: Some(Duration::from_millis(250)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert_error(result, "Unable to find the window with title \"window_name\" within 250ms");
+        assert_eq!(
+            *sleeps.borrow(),
+            vec![Duration::from_millis(100), Duration::from_millis(100), Duration::from_millis(50)],
+        );
+    }
+
+    #[test]
+    fn it_should_capture_until_the_content_settles_if_requested() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let captures = Rc::new(Cell::new(0));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(move || {
+                captures.set(captures.get() + 1);
+                // The content changes during the first 2 captures
+                let color = if captures.get() <= 2 { captures.get() as u8 } else { 255 };
+                Ok(image::RgbaImage::from_pixel(2, 2, image::Rgba([color, color, color, 255])))
+            });
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            settle: Some(SettleOptions { frames: 3, threshold: 0.0, timeout: Duration::from_secs(5) }),
+            keep_alpha: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(sleeps.borrow().len(), 4);
+        assert_eq!(
+            saved_images.borrow()[0].0,
+            DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, WHITE)),
+        );
+    }
+
+    #[test]
+    fn it_should_consider_captures_below_the_threshold_as_similar() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let captures = Rc::new(Cell::new(0));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(move || {
+                captures.set(captures.get() + 1);
+                // A blinking caret: one pixel out of four changes at each capture
+                let mut image = image::RgbaImage::from_pixel(2, 2, WHITE);
+                if captures.get() % 2 == 0 {
+                    image.put_pixel(0, 0, BLACK);
+                }
+                Ok(image)
+            });
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            settle: Some(SettleOptions { frames: 3, threshold: 0.25, timeout: Duration::from_secs(5) }),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(sleeps.borrow().len(), 2);
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_the_content_does_not_settle_in_time() {
+        // Given
+        let captures = Rc::new(Cell::new(0));
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(move || {
+                captures.set(captures.get() + 1);
+                let color = captures.get() as u8;
+                Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([color, color, color, 255])))
+            });
+        let fs_gateway = FakeFileSystemAdapter::new();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            settle: Some(SettleOptions { frames: 2, threshold: 0.0, timeout: Duration::from_millis(250) }),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_with(&mut usecase, options);
+
+        // Then
+        assert_error(result, "The content of the window did not settle within 250ms");
+    }
+
+    #[test]
+    fn it_should_capture_every_mapped_matching_window() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(vec![
+                WindowInfo { id: 0x10, ..window("Terminal", "XTerm") },
+                WindowInfo { id: 0x20, ..window("Terminal", "XTerm") },
+                WindowInfo { id: 0x30, ..window("Terminal", "XTerm") },
+            ]))
+            .with_is_window_mapped_result(|window_id| Ok(window_id != 0x20))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing_all(&mut usecase);
+
+        // Then
+        let Ok(ResultType::CaptureAllResult(captures)) = result else {
+            panic!("Unexpected result");
+        };
+        let results: Vec<_> = captures.iter().map(|capture| capture.result.clone()).collect();
+        assert_eq!(results, vec![Ok("Terminal-0x10.png".to_string()), Ok("Terminal-0x30.png".to_string())]);
+        assert_eq!(saved_images.borrow().len(), 2);
+    }
+
+    #[test]
+    fn it_should_report_the_failure_to_capture_one_of_the_windows() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(vec![
+                WindowInfo { id: 0x10, ..window("Terminal", "XTerm") },
+                WindowInfo { id: 0x20, ..window("Terminal", "XTerm") },
+            ]))
+            .with_is_window_mapped_result(|window_id| match window_id {
+                0x10 => Err(anyhow::anyhow!("Unable to get the window attributes of 0x10")),
+                _ => Ok(true),
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+
+        // When
+        let result = when_capturing_all(&mut usecase);
+
+        // Then
+        let Ok(ResultType::CaptureAllResult(captures)) = result else {
+            panic!("Unexpected result");
+        };
+        assert_eq!(captures[0].result, Err("Unable to get the window attributes of 0x10".to_string()));
+        assert_eq!(captures[1].result, Ok("Terminal-0x20.png".to_string()));
+    }
+
+    #[test]
+    fn it_should_capture_a_frame_shared_by_several_matching_windows_once() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(vec![
+                WindowInfo { id: 0x10, ..window("Terminal", "XTerm") },
+                WindowInfo { id: 0x11, ..window("Terminal", "XTerm") },
+                WindowInfo { id: 0x20, ..window("Terminal", "XTerm") },
+            ]))
+            .with_is_window_mapped_result(|_| Ok(true))
+            .with_get_frame_window_result(|window_id| match window_id {
+                0x10 | 0x11 => Ok(0x100),
+                _ => Ok(0x200),
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = usecase_with(window_system_gateway, fs_gateway);
+        let options = CaptureOptions {
+            with_frame: true,
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_capturing_all_with(&mut usecase, options);
+
+        // Then
+        let Ok(ResultType::CaptureAllResult(captures)) = result else {
+            panic!("Unexpected result");
+        };
+        let results: Vec<_> = captures.iter().map(|capture| capture.result.clone()).collect();
+        assert_eq!(results, vec![Ok("Terminal-0x10.png".to_string()), Ok("Terminal-0x20.png".to_string())]);
+        assert_eq!(saved_images.borrow().len(), 2);
+    }
+
+    #[test]
+    fn it_should_wait_for_any_matching_window_to_be_mapped_when_capturing_all() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(vec![
+                WindowInfo { id: 0x10, ..window("Terminal", "XTerm") },
+                WindowInfo { id: 0x20, ..window("Terminal", "XTerm") },
+            ]))
+            .with_is_window_mapped_result(|window_id| Ok(window_id == 0x20))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)));
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = usecase_with_clock(window_system_gateway, fs_gateway, clock_gateway);
+        let options = CaptureOptions {
+            wait_timeout: Some(Duration::from_secs(10)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when_capturing_all_with(&mut usecase, options);
+
+        // Then
+        let Ok(ResultType::CaptureAllResult(captures)) = result else {
+            panic!("Unexpected result");
+        };
+        let results: Vec<_> = captures.iter().map(|capture| capture.result.clone()).collect();
+        assert_eq!(results, vec![Ok("Terminal-0x20.png".to_string())]);
+        assert!(sleeps.borrow().is_empty());
+    }
+
+    #[test]
+    fn it_should_yield_an_error_if_no_window_matches_when_capturing_all() {
+        // Given
+        let window_system_gateway = FakeWindowSystemAdapter::new()
+            .with_find_windows_result(|_| Ok(Vec::new()));
+        let mut usecase = usecase_with(window_system_gateway, FakeFileSystemAdapter::new());
+
+        // When
+        let result = when_capturing_all(&mut usecase);
+
+        // Then
+        assert_error(result, "Unable to find the window with title \"Terminal\"");
+    }
+
+    fn monitor(name: &str, x: u32, primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            name: name.to_string(),
+            primary,
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    fn window(title: &str, class: &str) -> WindowInfo {
+        WindowInfo {
+            id: 1,
+            title: Some(title.to_string()),
+            instance: Some(class.to_lowercase()),
+            class: Some(class.to_string()),
+            pid: None,
+        }
+    }
+
+    /// The window titled "window_name", which is the one found by `FakeWindowSystemAdapter::capturing_window`.
+    pub fn title_target() -> CaptureTarget {
+        CaptureTarget::Search(WindowSelector::Title(WindowMatcher::new("window_name", MatchMode::Exact).unwrap()))
+    }
+
+    fn usecase_with(
+        window_system_gateway: FakeWindowSystemAdapter,
+        fs_gateway: FakeFileSystemAdapter,
+    ) -> TakeScreenShotUseCase {
+        usecase_with_clock(window_system_gateway, fs_gateway, FakeClockAdapter::new())
+    }
+
+    fn usecase_with_clock(
+        window_system_gateway: FakeWindowSystemAdapter,
+        fs_gateway: FakeFileSystemAdapter,
+        clock_gateway: FakeClockAdapter,
+    ) -> TakeScreenShotUseCase {
+        TakeScreenShotUseCase::new(Box::new(window_system_gateway), Box::new(fs_gateway), Box::new(clock_gateway))
     }
 
     fn when(usecase: &mut TakeScreenShotUseCase) -> anyhow::Result<ResultType> {
-        usecase.take_screenshot("window_name".to_string(), "output_path".to_string())
+        when_with(usecase, CaptureOptions::default())
+    }
+
+    fn when_with(usecase: &mut TakeScreenShotUseCase, options: CaptureOptions) -> anyhow::Result<ResultType> {
+        usecase.take_screenshot(title_target(), options, "output_path".to_string())
+    }
+
+    fn when_capturing(usecase: &mut TakeScreenShotUseCase, target: CaptureTarget) -> anyhow::Result<ResultType> {
+        usecase.take_screenshot(target, CaptureOptions::default(), "output_path".to_string())
+    }
+
+    fn when_capturing_all(usecase: &mut TakeScreenShotUseCase) -> anyhow::Result<ResultType> {
+        when_capturing_all_with(usecase, CaptureOptions::default())
+    }
+
+    fn when_capturing_all_with(
+        usecase: &mut TakeScreenShotUseCase,
+        options: CaptureOptions,
+    ) -> anyhow::Result<ResultType> {
+        usecase.take_screenshots_of_all_windows(
+            WindowSelector::Title(WindowMatcher::new("Terminal", MatchMode::Exact).unwrap()),
+            options,
+            "{title}-{id}.png",
+        )
     }
 
     pub fn assert_error<T>(
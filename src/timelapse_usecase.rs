@@ -0,0 +1,243 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::file_name_template::with_sequence_number;
+use crate::results::ResultType;
+use crate::stop_request::StopRequest;
+use crate::take_screen_shot_usecase::{CaptureOptions, TakeScreenShotUseCase};
+use crate::window_selector::CaptureTarget;
+
+// How often the stop request is checked while waiting for the next capture
+const STOP_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Periodically captures the target into numbered files, until the requested count is reached or a stop is requested
+/// (e.g. with Ctrl-C). A stop request never interrupts a capture that is being taken or saved.
+pub struct TimelapseUseCase {
+    take_screen_shot_usecase: TakeScreenShotUseCase,
+    stop_request: Arc<StopRequest>,
+}
+
+impl TimelapseUseCase {
+    pub fn new(take_screen_shot_usecase: TakeScreenShotUseCase, stop_request: Arc<StopRequest>) -> Self {
+        Self {
+            take_screen_shot_usecase,
+            stop_request,
+        }
+    }
+
+    /// The target is resolved once, after the delay if any. A stop is only accepted from then on. The captures are
+    /// spaced by `interval` from the end of the first one.
+    pub fn execute(
+        &mut self,
+        target: CaptureTarget,
+        options: CaptureOptions,
+        interval: Duration,
+        count: Option<u32>,
+        output_template: &str,
+    ) -> anyhow::Result<ResultType> {
+        if let Some(delay) = options.delay {
+            self.take_screen_shot_usecase.wait(delay)?;
+        }
+        let resolved_target = self.take_screen_shot_usecase.resolve_target(&target, &options)?;
+        self.stop_request.accept();
+        let mut saved_paths = Vec::new();
+        let mut start = None;
+        for seq in 1.. {
+            let usecase = &mut self.take_screen_shot_usecase;
+            let image_buffer = usecase.capture(&resolved_target, &options)?;
+            let path = usecase.resolve_output_path(
+                &with_sequence_number(output_template, seq)?,
+                resolved_target.client_window_id,
+            )?;
+            usecase.save(image_buffer, &options, &path)?;
+            saved_paths.push(path);
+
+            let start = *start.get_or_insert_with(|| self.take_screen_shot_usecase.clock_gateway.now());
+            if count.is_some_and(|count| seq >= count) || !self.wait_until(start + interval * seq) {
+                break;
+            }
+        }
+        Ok(ResultType::TimelapseResult(saved_paths))
+    }
+
+    /// Returns false if a stop has been requested meanwhile.
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let clock_gateway = &self.take_screen_shot_usecase.clock_gateway;
+        loop {
+            if self.stop_request.is_requested() {
+                return false;
+            }
+            let now = clock_gateway.now();
+            if now >= deadline {
+                return true;
+            }
+            clock_gateway.sleep((deadline - now).min(STOP_POLLING_INTERVAL));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::clock::fake_clock_adapter::FakeClockAdapter;
+    use crate::fs::fake_file_system_adapter::FakeFileSystemAdapter;
+    use crate::results::ResultType;
+    use crate::stop_request::StopRequest;
+    use crate::take_screen_shot_usecase::tests::title_target;
+    use crate::take_screen_shot_usecase::{CaptureOptions, TakeScreenShotUseCase};
+    use crate::timelapse_usecase::TimelapseUseCase;
+    use crate::window_system::fake_window_system_adapter::FakeWindowSystemAdapter;
+
+    #[test]
+    fn it_should_capture_the_requested_number_of_numbered_files() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = TimelapseUseCase::new(
+            TakeScreenShotUseCase::new(
+                Box::new(FakeWindowSystemAdapter::capturing_window(1)),
+                Box::new(fs_gateway),
+                Box::new(clock_gateway),
+            ),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase, CaptureOptions::default(), Some(3));
+
+        // Then
+        let expected_paths = vec!["capture-0001.png", "capture-0002.png", "capture-0003.png"];
+        match result {
+            Ok(ResultType::TimelapseResult(paths)) => assert_eq!(paths, expected_paths),
+            _ => panic!("Unexpected result"),
+        }
+        let saved_paths: Vec<_> = saved_images.borrow().iter().map(|(_, path)| path.clone()).collect();
+        assert_eq!(saved_paths, expected_paths);
+        assert_eq!(sleeps.borrow().iter().sum::<Duration>(), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn it_should_look_for_the_window_once() {
+        // Given
+        let searches = Rc::new(Cell::new(0));
+        let finder_searches = searches.clone();
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(move |_| {
+                finder_searches.set(finder_searches.get() + 1);
+                Ok(Some(1))
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)))
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = TimelapseUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase, CaptureOptions::default(), Some(3));
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(searches.get(), 1);
+    }
+
+    #[test]
+    fn it_should_stop_once_the_current_capture_is_saved_when_requested() {
+        // Given
+        let stop_request = Arc::new(StopRequest::new());
+        let fs_stop_request = stop_request.clone();
+        let fs_gateway = FakeFileSystemAdapter::new()
+            .with_result(Box::new(move || {
+                fs_stop_request.request();
+                Ok(())
+            }));
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = TimelapseUseCase::new(
+            TakeScreenShotUseCase::new(
+                Box::new(FakeWindowSystemAdapter::capturing_window(1)),
+                Box::new(fs_gateway),
+                Box::new(FakeClockAdapter::new()),
+            ),
+            stop_request,
+        );
+
+        // When
+        let result = when(&mut usecase, CaptureOptions::default(), None);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(saved_images.borrow().len(), 1);
+    }
+
+    #[test]
+    fn it_should_only_wait_for_the_delay_before_the_first_capture() {
+        // Given
+        let clock_gateway = FakeClockAdapter::new();
+        let sleeps = clock_gateway.sleeps.clone();
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let mut usecase = TimelapseUseCase::new(
+            TakeScreenShotUseCase::new(
+                Box::new(FakeWindowSystemAdapter::capturing_window(1)),
+                Box::new(fs_gateway),
+                Box::new(clock_gateway),
+            ),
+            Arc::new(StopRequest::new()),
+        );
+        let options = CaptureOptions {
+            delay: Some(Duration::from_secs(10)),
+            ..CaptureOptions::default()
+        };
+
+        // When
+        let result = when(&mut usecase, options, Some(3));
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(sleeps.borrow()[0], Duration::from_secs(10));
+        assert_eq!(sleeps.borrow().iter().sum::<Duration>(), Duration::from_secs(14));
+    }
+
+    #[test]
+    fn it_should_report_capture_failures() {
+        // Given
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+        );
+        let mut usecase = TimelapseUseCase::new(
+            TakeScreenShotUseCase::new(
+                window_system_gateway,
+                Box::new(FakeFileSystemAdapter::new()),
+                Box::new(FakeClockAdapter::new()),
+            ),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase, CaptureOptions::default(), Some(3));
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(result.err().unwrap().to_string(), "Unable to take screenshot.");
+    }
+
+    fn when(
+        usecase: &mut TimelapseUseCase,
+        options: CaptureOptions,
+        count: Option<u32>,
+    ) -> anyhow::Result<ResultType> {
+        usecase.execute(
+            title_target(),
+            options,
+            Duration::from_secs(2),
+            count,
+            "capture.png",
+        )
+    }
+}
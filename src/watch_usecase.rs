@@ -0,0 +1,248 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::file_name_template::with_sequence_number;
+use crate::results::ResultType;
+use crate::stop_request::StopRequest;
+use crate::take_screen_shot_usecase::{CaptureOptions, TakeScreenShotUseCase};
+use crate::window_selector::CaptureTarget;
+
+// How often the stop request is checked while waiting for a change
+const STOP_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Captures the target into numbered files each time its content changes (as reported by the window system, without
+/// polling), until the requested count is reached or a stop is requested (e.g. with Ctrl-C).
+/// A change which leaves the capture identical to the previous one (e.g. outside of the captured region) is not saved.
+pub struct WatchUseCase {
+    take_screen_shot_usecase: TakeScreenShotUseCase,
+    stop_request: Arc<StopRequest>,
+}
+
+impl WatchUseCase {
+    pub fn new(take_screen_shot_usecase: TakeScreenShotUseCase, stop_request: Arc<StopRequest>) -> Self {
+        Self {
+            take_screen_shot_usecase,
+            stop_request,
+        }
+    }
+
+    /// The initial content is saved first. With a debounce, a burst of changes is only captured once the content has
+    /// not changed for that long.
+    pub fn execute(
+        &mut self,
+        target: CaptureTarget,
+        options: CaptureOptions,
+        debounce: Option<Duration>,
+        count: Option<u32>,
+        output_template: &str,
+    ) -> anyhow::Result<ResultType> {
+        let resolved_target = self.take_screen_shot_usecase.resolve_target(&target, &options)?;
+        self.stop_request.accept();
+        // Starts watching before the first capture, so that no change is missed
+        self.take_screen_shot_usecase.window_system_gateway.wait_for_damage(resolved_target.window_id, Duration::ZERO)?;
+
+        let mut saved_paths = Vec::new();
+        let mut previous = None;
+        loop {
+            let image_buffer = self.take_screen_shot_usecase.capture(&resolved_target, &options)?;
+            if previous.as_ref() != Some(&image_buffer) {
+                let path = self.take_screen_shot_usecase.resolve_output_path(
+                    &with_sequence_number(output_template, saved_paths.len() as u32 + 1)?,
+                    resolved_target.client_window_id,
+                )?;
+                self.take_screen_shot_usecase.save(image_buffer.clone(), &options, &path)?;
+                saved_paths.push(path);
+                previous = Some(image_buffer);
+                if count.is_some_and(|count| saved_paths.len() as u32 >= count) {
+                    break;
+                }
+            }
+            if !self.wait_for_change(resolved_target.window_id, debounce)? {
+                break;
+            }
+        }
+        Ok(ResultType::TimelapseResult(saved_paths))
+    }
+
+    /// Returns false if a stop has been requested meanwhile.
+    fn wait_for_change(&self, window_id: u64, debounce: Option<Duration>) -> anyhow::Result<bool> {
+        let window_system_gateway = &self.take_screen_shot_usecase.window_system_gateway;
+        loop {
+            if self.stop_request.is_requested() {
+                return Ok(false);
+            }
+            if window_system_gateway.wait_for_damage(window_id, STOP_POLLING_INTERVAL)? {
+                break;
+            }
+        }
+        if let Some(debounce) = debounce {
+            while window_system_gateway.wait_for_damage(window_id, debounce)? {
+                if self.stop_request.is_requested() {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::clock::fake_clock_adapter::FakeClockAdapter;
+    use crate::fs::fake_file_system_adapter::FakeFileSystemAdapter;
+    use crate::results::ResultType;
+    use crate::stop_request::StopRequest;
+    use crate::take_screen_shot_usecase::tests::title_target;
+    use crate::take_screen_shot_usecase::{CaptureOptions, TakeScreenShotUseCase};
+    use crate::watch_usecase::WatchUseCase;
+    use crate::window_system::fake_window_system_adapter::FakeWindowSystemAdapter;
+
+    #[test]
+    fn it_should_save_the_initial_content_and_each_change() {
+        // Given
+        let captures = Cell::new(0u8);
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_wait_for_damage_result(|_, _| Ok(true))
+            .with_take_screen_shot_result(move || {
+                captures.set(captures.get() + 1);
+                Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([captures.get(), 0, 0, 255])))
+            })
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = WatchUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase, None, Some(3));
+
+        // Then
+        let expected_paths = vec!["change-0001.png", "change-0002.png", "change-0003.png"];
+        match result {
+            Ok(ResultType::TimelapseResult(paths)) => assert_eq!(paths, expected_paths),
+            _ => panic!("Unexpected result"),
+        }
+        assert_eq!(saved_images.borrow().len(), 3);
+    }
+
+    #[test]
+    fn it_should_not_save_the_changes_leaving_the_capture_identical() {
+        // Given
+        let stop_request = Arc::new(StopRequest::new());
+        let damage_stop_request = stop_request.clone();
+        let damages = Cell::new(0);
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_wait_for_damage_result(move |_, _| {
+                damages.set(damages.get() + 1);
+                if damages.get() > 3 {
+                    damage_stop_request.request();
+                }
+                Ok(true)
+            })
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)))
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = WatchUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            stop_request,
+        );
+
+        // When
+        let result = when(&mut usecase, None, None);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(saved_images.borrow().len(), 1);
+    }
+
+    #[test]
+    fn it_should_wait_for_the_changes_to_stop_for_the_debounce_duration() {
+        // Given
+        let stop_request = Arc::new(StopRequest::new());
+        let damage_stop_request = stop_request.clone();
+        // The initial watch, the first change, two more changes within the debounce duration, then nothing
+        let damage_results = RefCell::new(VecDeque::from([false, true, true, true, false]));
+        let timeouts = Rc::new(RefCell::new(Vec::new()));
+        let timeouts_spy = timeouts.clone();
+        let captures = Cell::new(0u8);
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_wait_for_damage_result(move |_, timeout| {
+                timeouts_spy.borrow_mut().push(timeout);
+                let damaged = damage_results.borrow_mut().pop_front();
+                if damaged.is_none() {
+                    damage_stop_request.request();
+                }
+                Ok(damaged.unwrap_or(false))
+            })
+            .with_take_screen_shot_result(move || {
+                captures.set(captures.get() + 1);
+                Ok(image::RgbaImage::from_pixel(1, 1, image::Rgba([captures.get(), 0, 0, 255])))
+            })
+        );
+        let fs_gateway = FakeFileSystemAdapter::saving();
+        let saved_images = fs_gateway.vec.clone();
+        let mut usecase = WatchUseCase::new(
+            TakeScreenShotUseCase::new(window_system_gateway, Box::new(fs_gateway), Box::new(FakeClockAdapter::new())),
+            stop_request,
+        );
+
+        // When
+        let result = when(&mut usecase, Some(Duration::from_millis(500)), None);
+
+        // Then
+        assert!(result.is_ok());
+        assert_eq!(saved_images.borrow().len(), 2);
+        let debounce_waits = timeouts.borrow().iter().filter(|timeout| **timeout == Duration::from_millis(500)).count();
+        assert_eq!(debounce_waits, 3);
+    }
+
+    #[test]
+    fn it_should_report_watching_failures() {
+        // Given
+        let window_system_gateway = Box::new(FakeWindowSystemAdapter::new()
+            .with_find_window_result(|_| Ok(Some(1)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)))
+        );
+        let mut usecase = WatchUseCase::new(
+            TakeScreenShotUseCase::new(
+                window_system_gateway,
+                Box::new(FakeFileSystemAdapter::new()),
+                Box::new(FakeClockAdapter::new()),
+            ),
+            Arc::new(StopRequest::new()),
+        );
+
+        // When
+        let result = when(&mut usecase, None, None);
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(result.err().unwrap().to_string(), "Unable to watch the window changes.");
+    }
+
+    fn when(
+        usecase: &mut WatchUseCase,
+        debounce: Option<Duration>,
+        count: Option<u32>,
+    ) -> anyhow::Result<ResultType> {
+        usecase.execute(
+            title_target(),
+            CaptureOptions::default(),
+            debounce,
+            count,
+            "change.png",
+        )
+    }
+}
@@ -0,0 +1,106 @@
+use regex::Regex;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MatchMode {
+    Exact,
+    Substring,
+    CaseInsensitive,
+    Regex,
+}
+
+/// Matches window properties (like the title) against a pattern, according to a `MatchMode`.
+#[derive(Clone, Debug)]
+pub struct WindowMatcher {
+    pattern: String,
+    mode: MatchMode,
+    regex: Option<Regex>,
+}
+
+impl WindowMatcher {
+    pub fn new(pattern: &str, mode: MatchMode) -> anyhow::Result<WindowMatcher> {
+        let regex = match mode {
+            MatchMode::Regex => Some(
+                Regex::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid regex {:?}: {}", pattern, e))?
+            ),
+            _ => None,
+        };
+        Ok(WindowMatcher {
+            pattern: pattern.to_string(),
+            mode,
+            regex,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn matches(&self, candidate: &str) -> bool {
+        match self.mode {
+            MatchMode::Exact => candidate == self.pattern,
+            MatchMode::Substring => candidate.contains(&self.pattern),
+            MatchMode::CaseInsensitive => candidate.to_lowercase() == self.pattern.to_lowercase(),
+            MatchMode::Regex => self.regex.as_ref().is_some_and(|regex| regex.is_match(candidate)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::window_matcher::{MatchMode, WindowMatcher};
+
+    #[test]
+    fn it_should_match_exact_titles_only() {
+        // Given
+        let matcher = WindowMatcher::new("editor", MatchMode::Exact).unwrap();
+
+        // Then
+        assert!(matcher.matches("editor"));
+        assert!(!matcher.matches("editor*"));
+        assert!(!matcher.matches("Editor"));
+    }
+
+    #[test]
+    fn it_should_match_substrings() {
+        // Given
+        let matcher = WindowMatcher::new("editor", MatchMode::Substring).unwrap();
+
+        // Then
+        assert!(matcher.matches("editor"));
+        assert!(matcher.matches("main.rs - editor*"));
+        assert!(!matcher.matches("Editor"));
+    }
+
+    #[test]
+    fn it_should_match_ignoring_case() {
+        // Given
+        let matcher = WindowMatcher::new("Editor", MatchMode::CaseInsensitive).unwrap();
+
+        // Then
+        assert!(matcher.matches("editor"));
+        assert!(matcher.matches("EDITOR"));
+        assert!(!matcher.matches("editor*"));
+    }
+
+    #[test]
+    fn it_should_match_regexes() {
+        // Given
+        let matcher = WindowMatcher::new(r"^Firefox( - .*)?$", MatchMode::Regex).unwrap();
+
+        // Then
+        assert!(matcher.matches("Firefox"));
+        assert!(matcher.matches("Firefox - Borkscreen"));
+        assert!(!matcher.matches("Mozilla Firefox"));
+    }
+
+    #[test]
+    fn it_should_reject_invalid_regexes() {
+        // When
+        let result = WindowMatcher::new("(unclosed", MatchMode::Regex);
+
+        // Then
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().starts_with("Invalid regex \"(unclosed\""));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::monitor_selector::MonitorSelector;
+use crate::results::WindowInfo;
+use crate::window_matcher::WindowMatcher;
+
+/// Describes which window the user is looking for.
+#[derive(Clone, Debug)]
+pub enum WindowSelector {
+    Title(WindowMatcher),
+    /// Matches either the instance or the class name of the `WM_CLASS` property.
+    Class(WindowMatcher),
+    /// Matches the `_NET_WM_PID` property. The child processes of `pid` are resolved by the window
+    /// system adapter, as they are not part of the window information.
+    Pid { pid: u32, include_children: bool },
+}
+
+/// Describes what the user wants to capture.
+#[derive(Clone, Debug)]
+pub enum CaptureTarget {
+    Search(WindowSelector),
+    WindowId(u64),
+    /// The window currently focused by the user.
+    Active,
+    /// The window the user clicks on.
+    Pick,
+    /// The whole screen (root window) with the given number, or the default screen.
+    Screen(Option<i32>),
+    /// The part of the default screen displayed by the given monitor.
+    Monitor(MonitorSelector),
+}
+
+impl WindowSelector {
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        match self {
+            WindowSelector::Title(matcher) => window.title
+                .as_ref()
+                .is_some_and(|title| matcher.matches(title)),
+            WindowSelector::Class(matcher) => [&window.instance, &window.class]
+                .into_iter()
+                .flatten()
+                .any(|name| matcher.matches(name)),
+            WindowSelector::Pid { pid, .. } => window.pid == Some(*pid),
+        }
+    }
+}
+
+impl fmt::Display for WindowSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowSelector::Title(matcher) => write!(f, "title {:?}", matcher.pattern()),
+            WindowSelector::Class(matcher) => write!(f, "class {:?}", matcher.pattern()),
+            WindowSelector::Pid { pid, include_children: false } => write!(f, "pid {}", pid),
+            WindowSelector::Pid { pid, include_children: true } => write!(f, "pid {} or one of its child processes", pid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::results::WindowInfo;
+    use crate::window_matcher::{MatchMode, WindowMatcher};
+    use crate::window_selector::WindowSelector;
+
+    #[test]
+    fn it_should_select_windows_by_title() {
+        // Given
+        let selector = WindowSelector::Title(WindowMatcher::new("bash", MatchMode::Exact).unwrap());
+
+        // Then
+        assert!(selector.matches(&window(Some("bash"), Some("xterm"), Some("XTerm"))));
+        assert!(!selector.matches(&window(Some("vim"), Some("bash"), Some("bash"))));
+        assert!(!selector.matches(&window(None, Some("bash"), Some("bash"))));
+    }
+
+    #[test]
+    fn it_should_select_windows_by_instance_or_class() {
+        // Given
+        let selector = WindowSelector::Class(WindowMatcher::new("xterm", MatchMode::CaseInsensitive).unwrap());
+
+        // Then
+        assert!(selector.matches(&window(Some("bash"), Some("xterm"), Some("XTerm"))));
+        assert!(selector.matches(&window(Some("bash"), Some("main"), Some("XTerm"))));
+        assert!(selector.matches(&window(None, None, Some("XTerm"))));
+        assert!(!selector.matches(&window(Some("xterm"), Some("feh"), Some("feh"))));
+        assert!(!selector.matches(&window(Some("xterm"), None, None)));
+    }
+
+    #[test]
+    fn it_should_select_windows_by_pid() {
+        // Given
+        let selector = WindowSelector::Pid { pid: 42, include_children: true };
+        let mut with_pid = window(Some("bash"), Some("xterm"), Some("XTerm"));
+        with_pid.pid = Some(42);
+        let mut with_other_pid = with_pid.clone();
+        with_other_pid.pid = Some(43);
+
+        // Then
+        assert!(selector.matches(&with_pid));
+        assert!(!selector.matches(&with_other_pid));
+        assert!(!selector.matches(&window(Some("bash"), Some("xterm"), Some("XTerm"))));
+    }
+
+    #[test]
+    fn it_should_describe_the_selector() {
+        // Given
+        let by_title = WindowSelector::Title(WindowMatcher::new("bash", MatchMode::Exact).unwrap());
+        let by_class = WindowSelector::Class(WindowMatcher::new("xterm", MatchMode::Exact).unwrap());
+
+        // Then
+        assert_eq!(by_title.to_string(), "title \"bash\"");
+        assert_eq!(by_class.to_string(), "class \"xterm\"");
+        assert_eq!(WindowSelector::Pid { pid: 42, include_children: false }.to_string(), "pid 42");
+        assert_eq!(
+            WindowSelector::Pid { pid: 42, include_children: true }.to_string(),
+            "pid 42 or one of its child processes",
+        );
+    }
+
+    fn window(title: Option<&str>, instance: Option<&str>, class: Option<&str>) -> WindowInfo {
+        WindowInfo {
+            id: 1,
+            title: title.map(str::to_string),
+            instance: instance.map(str::to_string),
+            class: class.map(str::to_string),
+            pid: None,
+        }
+    }
+}
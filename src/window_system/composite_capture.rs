@@ -0,0 +1,97 @@
+use std::os::raw::c_int;
+use std::rc::Rc;
+
+use libloading::Library;
+use x11_dl::xlib::{self, Xlib};
+
+use crate::window_system::x_errors::trap_x_errors;
+
+// https://gitlab.freedesktop.org/xorg/proto/xorgproto/-/blob/master/include/X11/extensions/composite.h
+const COMPOSITE_REDIRECT_AUTOMATIC: c_int = 0;
+
+// x11-dl has no bindings for libXcomposite, so the few functions used are loaded by hand
+type QueryExtension = unsafe extern "C" fn(*mut xlib::Display, *mut c_int, *mut c_int) -> xlib::Bool;
+type QueryVersion = unsafe extern "C" fn(*mut xlib::Display, *mut c_int, *mut c_int) -> xlib::Status;
+type RedirectWindow = unsafe extern "C" fn(*mut xlib::Display, xlib::Window, c_int);
+type UnredirectWindow = unsafe extern "C" fn(*mut xlib::Display, xlib::Window, c_int);
+type NameWindowPixmap = unsafe extern "C" fn(*mut xlib::Display, xlib::Window) -> xlib::Pixmap;
+
+/// Gives access to the off-screen contents of the windows (Composite extension), so that windows obscured by other
+/// windows or partially off-screen are captured as they would look like if they were fully visible.
+pub struct CompositeCapture {
+    xlib: Rc<Xlib>,
+    display: *mut xlib::Display,
+    redirect_window: RedirectWindow,
+    unredirect_window: UnredirectWindow,
+    name_window_pixmap: NameWindowPixmap,
+    // Keeps the functions above loaded
+    _library: Library,
+}
+
+impl CompositeCapture {
+    /// Returns `None` if the Xcomposite library cannot be loaded, or if the X server does not support
+    /// Composite 0.2 (the first version to have `XCompositeNameWindowPixmap`).
+    pub fn new(xlib: Rc<Xlib>, display: *mut xlib::Display) -> Option<CompositeCapture> {
+        unsafe {
+            let library = Library::new("libXcomposite.so.1")
+                .or_else(|_| Library::new("libXcomposite.so"))
+                .ok()?;
+            let query_extension = *library.get::<QueryExtension>(b"XCompositeQueryExtension\0").ok()?;
+            let query_version = *library.get::<QueryVersion>(b"XCompositeQueryVersion\0").ok()?;
+            let redirect_window = *library.get::<RedirectWindow>(b"XCompositeRedirectWindow\0").ok()?;
+            let unredirect_window = *library.get::<UnredirectWindow>(b"XCompositeUnredirectWindow\0").ok()?;
+            let name_window_pixmap = *library.get::<NameWindowPixmap>(b"XCompositeNameWindowPixmap\0").ok()?;
+
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if query_extension(display, &mut event_base, &mut error_base) == 0 {
+                return None;
+            }
+            let mut major = 0;
+            let mut minor = 2;
+            if query_version(display, &mut major, &mut minor) == 0 || (major, minor) < (0, 2) {
+                return None;
+            }
+            Some(CompositeCapture {
+                xlib,
+                display,
+                redirect_window,
+                unredirect_window,
+                name_window_pixmap,
+                _library: library,
+            })
+        }
+    }
+
+    /// Redirects the window off-screen, until `unredirect_window` is called. The redirection is automatic, so the
+    /// window is still displayed as usual.
+    /// Unless another client (e.g. a compositing manager) already redirected it, the window is repainted as a whole,
+    /// its obscured parts not being on the screen yet.
+    pub fn redirect_window(&self, window: xlib::Window) -> anyhow::Result<()> {
+        let (_, failed) = trap_x_errors(&self.xlib, self.display, || unsafe {
+            (self.redirect_window)(self.display, window, COMPOSITE_REDIRECT_AUTOMATIC)
+        });
+        if failed {
+            anyhow::bail!("Unable to redirect the window {:#x} off-screen", window);
+        }
+        Ok(())
+    }
+
+    pub fn unredirect_window(&self, window: xlib::Window) {
+        trap_x_errors(&self.xlib, self.display, || unsafe {
+            (self.unredirect_window)(self.display, window, COMPOSITE_REDIRECT_AUTOMATIC)
+        });
+    }
+
+    /// Returns a pixmap holding the contents of the redirected window (including its border), which has to be freed
+    /// with `XFreePixmap`.
+    pub fn name_window_pixmap(&self, window: xlib::Window) -> anyhow::Result<xlib::Pixmap> {
+        let (pixmap, failed) = trap_x_errors(&self.xlib, self.display, || unsafe {
+            (self.name_window_pixmap)(self.display, window)
+        });
+        if failed || pixmap == 0 {
+            anyhow::bail!("Unable to get the off-screen contents of the window {:#x}, it may not be mapped", window);
+        }
+        Ok(pixmap)
+    }
+}
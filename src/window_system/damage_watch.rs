@@ -0,0 +1,200 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::os::raw::{c_int, c_ulong};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use libloading::Library;
+use x11_dl::xlib::{self, Xlib};
+
+use crate::window_system::x_errors::trap_x_errors;
+
+// https://gitlab.freedesktop.org/xorg/proto/xorgproto/-/blob/master/include/X11/extensions/damagewire.h
+const X_DAMAGE_NOTIFY: c_int = 0;
+const X_DAMAGE_REPORT_NON_EMPTY: c_int = 3;
+
+type Damage = c_ulong;
+
+// x11-dl has no bindings for libXdamage, so the few functions used are loaded by hand
+type QueryExtension = unsafe extern "C" fn(*mut xlib::Display, *mut c_int, *mut c_int) -> xlib::Bool;
+type QueryVersion = unsafe extern "C" fn(*mut xlib::Display, *mut c_int, *mut c_int) -> xlib::Status;
+type Create = unsafe extern "C" fn(*mut xlib::Display, xlib::Drawable, c_int) -> Damage;
+type Subtract = unsafe extern "C" fn(*mut xlib::Display, Damage, c_ulong, c_ulong);
+
+// The leading fields of XDamageNotifyEvent (Xdamage.h), up to the only ones read
+#[allow(dead_code)]
+#[repr(C)]
+struct DamageNotifyEvent {
+    type_: c_int,
+    serial: c_ulong,
+    send_event: xlib::Bool,
+    display: *mut xlib::Display,
+    drawable: xlib::Drawable,
+    damage: Damage,
+}
+
+// The events are read through this mirror, so a misplaced field would go unnoticed (every field up to the damage is
+// as large as a long, once padded)
+const _: () = assert!(std::mem::offset_of!(DamageNotifyEvent, damage) == 5 * std::mem::size_of::<c_ulong>());
+const _: () = assert!(std::mem::size_of::<DamageNotifyEvent>() <= std::mem::size_of::<xlib::XEvent>());
+
+/// Reports the changes of the contents of the windows (Damage extension), so that they are only captured when needed.
+pub struct DamageWatch {
+    xlib: Rc<Xlib>,
+    display: *mut xlib::Display,
+    event_base: c_int,
+    create: Create,
+    subtract: Subtract,
+    damages: RefCell<HashMap<xlib::Window, Damage>>,
+    // Keeps the functions above loaded
+    _library: Library,
+}
+
+impl DamageWatch {
+    /// Returns `None` if the Xdamage library cannot be loaded, or if the X server does not support Damage 1.1.
+    pub fn new(xlib: Rc<Xlib>, display: *mut xlib::Display) -> Option<DamageWatch> {
+        unsafe {
+            let library = Library::new("libXdamage.so.1")
+                .or_else(|_| Library::new("libXdamage.so"))
+                .ok()?;
+            let query_extension = *library.get::<QueryExtension>(b"XDamageQueryExtension\0").ok()?;
+            let query_version = *library.get::<QueryVersion>(b"XDamageQueryVersion\0").ok()?;
+            let create = *library.get::<Create>(b"XDamageCreate\0").ok()?;
+            let subtract = *library.get::<Subtract>(b"XDamageSubtract\0").ok()?;
+
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if query_extension(display, &mut event_base, &mut error_base) == 0 {
+                return None;
+            }
+            // The version has to be negotiated before any other request
+            let mut major = 1;
+            let mut minor = 1;
+            if query_version(display, &mut major, &mut minor) == 0 || (major, minor) < (1, 1) {
+                return None;
+            }
+            Some(DamageWatch {
+                xlib,
+                display,
+                event_base,
+                create,
+                subtract,
+                damages: RefCell::new(HashMap::new()),
+                _library: library,
+            })
+        }
+    }
+
+    /// Waits for the contents of the window to change, for at most `timeout`, and returns whether they did.
+    /// The window is watched from the first call on, until the connection to the X server is closed, and the changes
+    /// made between two calls are reported by the second one.
+    pub fn wait_for_damage(&self, window: xlib::Window, timeout: Duration) -> anyhow::Result<bool> {
+        let damage = self.get_damage(window)?;
+        let deadline = Instant::now() + timeout;
+        unsafe {
+            loop {
+                let mut damaged = false;
+                while (self.xlib.XPending)(self.display) > 0 {
+                    let mut event: xlib::XEvent = std::mem::zeroed();
+                    (self.xlib.XNextEvent)(self.display, &mut event);
+                    if notified_damage(&event, self.event_base) == Some(damage) {
+                        damaged = true;
+                    }
+                }
+                if damaged {
+                    // Empties the damaged region, so that the next change is notified again
+                    (self.subtract)(self.display, damage, 0, 0);
+                    (self.xlib.XFlush)(self.display);
+                    return Ok(true);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(false);
+                }
+                let mut poll_fd = libc::pollfd {
+                    fd: (self.xlib.XConnectionNumber)(self.display),
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let timeout_ms = (deadline - now).as_millis().clamp(1, c_int::MAX as u128) as c_int;
+                if libc::poll(&mut poll_fd, 1, timeout_ms) < 0 {
+                    let error = std::io::Error::last_os_error();
+                    if error.kind() != std::io::ErrorKind::Interrupted {
+                        anyhow::bail!("Unable to wait for the X server events: {}", error);
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_damage(&self, window: xlib::Window) -> anyhow::Result<Damage> {
+        if let Some(damage) = self.damages.borrow().get(&window) {
+            return Ok(*damage);
+        }
+        let (damage, failed) = trap_x_errors(&self.xlib, self.display, || unsafe {
+            (self.create)(self.display, window, X_DAMAGE_REPORT_NON_EMPTY)
+        });
+        if failed || damage == 0 {
+            anyhow::bail!("Unable to watch the changes of the window {:#x}", window);
+        }
+        self.damages.borrow_mut().insert(window, damage);
+        Ok(damage)
+    }
+}
+
+// The damage the event notifies a change of, if it is a damage notification
+fn notified_damage(event: &xlib::XEvent, event_base: c_int) -> Option<Damage> {
+    let notify = unsafe { &*(event as *const xlib::XEvent as *const DamageNotifyEvent) };
+    (notify.type_ == event_base + X_DAMAGE_NOTIFY).then_some(notify.damage)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::raw::c_long;
+
+    use x11_dl::xlib;
+
+    use crate::window_system::damage_watch::notified_damage;
+
+    const EVENT_BASE: i32 = 91;
+
+    #[test]
+    fn it_should_read_the_damage_of_a_damage_notification() {
+        // Given
+        let event = event(EVENT_BASE, 0x600001);
+
+        // When
+        let damage = notified_damage(&event, EVENT_BASE);
+
+        // Then
+        assert_eq!(damage, Some(0x600001));
+    }
+
+    #[test]
+    fn it_should_ignore_the_other_events() {
+        // Given
+        let event = event(xlib::Expose, 0x600001);
+
+        // When
+        let damage = notified_damage(&event, EVENT_BASE);
+
+        // Then
+        assert_eq!(damage, None);
+    }
+
+    // As Xlib delivers it: the header shared by every event, then the damage right after the drawable
+    fn event(type_: i32, damage: c_long) -> xlib::XEvent {
+        let mut event = xlib::XEvent { pad: [0; 24] };
+        event.any = xlib::XAnyEvent {
+            type_,
+            serial: 1,
+            send_event: xlib::False,
+            display: std::ptr::null_mut(),
+            window: 0x400001,
+        };
+        unsafe {
+            event.pad[5] = damage;
+        }
+        event
+    }
+}
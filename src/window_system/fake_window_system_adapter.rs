@@ -1,48 +1,206 @@
-use crate::gateways::{ListWindowsWindowSystemGateway, ScreenShotWindowSystemGateway};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::cursor::Cursor;
+use crate::gateways::{ListMonitorsWindowSystemGateway, ListWindowsWindowSystemGateway, ScreenShotWindowSystemGateway};
+use crate::region::Region;
+use crate::results::{MonitorInfo, WindowInfo};
+use crate::window_selector::WindowSelector;
+
+type FindWindowResult = Box<dyn Fn(&WindowSelector) -> anyhow::Result<Option<u64>>>;
+type FindWindowsResult = Box<dyn Fn(&WindowSelector) -> anyhow::Result<Vec<WindowInfo>>>;
 
 pub struct FakeWindowSystemAdapter {
-    find_window_result: Box<dyn Fn() -> anyhow::Result<Option<u64>>>,
-    take_screen_shot_result: Box<dyn Fn() -> anyhow::Result<image::RgbImage>>,
-    list_windows_result: Box<dyn Fn() -> anyhow::Result<Vec<String>>>,
+    find_window_result: FindWindowResult,
+    find_windows_result: FindWindowsResult,
+    get_active_window_result: Box<dyn Fn() -> anyhow::Result<Option<u64>>>,
+    pick_window_result: Box<dyn Fn() -> anyhow::Result<Option<u64>>>,
+    get_root_window_result: Box<dyn Fn(Option<i32>) -> anyhow::Result<u64>>,
+    get_frame_window_result: Box<dyn Fn(u64) -> anyhow::Result<u64>>,
+    window_exists_result: Box<dyn Fn(u64) -> anyhow::Result<bool>>,
+    get_window_info_result: Box<dyn Fn(u64) -> anyhow::Result<WindowInfo>>,
+    is_window_mapped_result: Box<dyn Fn(u64) -> anyhow::Result<bool>>,
+    take_screen_shot_result: Box<dyn Fn() -> anyhow::Result<image::RgbaImage>>,
+    get_cursor_result: Box<dyn Fn(u64) -> anyhow::Result<Cursor>>,
+    wait_for_damage_result: Box<dyn Fn(u64, Duration) -> anyhow::Result<bool>>,
+    list_windows_result: Box<dyn Fn() -> anyhow::Result<Vec<WindowInfo>>>,
+    list_monitors_result: Box<dyn Fn() -> anyhow::Result<Vec<MonitorInfo>>>,
+    take_screen_shot_region_spy: Rc<RefCell<Option<Region>>>,
+    take_screen_shot_window_spy: Rc<Cell<Option<u64>>>,
 }
 
 impl FakeWindowSystemAdapter {
     pub fn new() -> Self {
         Self {
-            find_window_result: Box::new(|| { Err(anyhow::anyhow!("Unable to list windows.")) }),
+            find_window_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to list windows.")) }),
+            find_windows_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to list windows.")) }),
+            get_active_window_result: Box::new(|| { Err(anyhow::anyhow!("Unable to get the active window.")) }),
+            pick_window_result: Box::new(|| { Err(anyhow::anyhow!("Unable to pick a window.")) }),
+            get_root_window_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to get the root window.")) }),
+            get_frame_window_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to get the frame window.")) }),
+            window_exists_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to check the window existence.")) }),
+            get_window_info_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to get the window information.")) }),
+            is_window_mapped_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to check the window map state.")) }),
             take_screen_shot_result: Box::new(|| { Err(anyhow::anyhow!("Unable to take screenshot.")) }),
+            get_cursor_result: Box::new(|_| { Err(anyhow::anyhow!("Unable to get the cursor.")) }),
+            wait_for_damage_result: Box::new(|_, _| { Err(anyhow::anyhow!("Unable to watch the window changes.")) }),
             list_windows_result: Box::new(|| { Err(anyhow::anyhow!("Unable to list windows.")) }),
+            list_monitors_result: Box::new(|| { Err(anyhow::anyhow!("Unable to list monitors.")) }),
+            take_screen_shot_region_spy: Rc::new(RefCell::new(None)),
+            take_screen_shot_window_spy: Rc::new(Cell::new(None)),
         }
     }
+    /// Finds any searched window with the given id, and captures it as a single transparent pixel.
+    pub fn capturing_window(window_id: u64) -> Self {
+        Self::new()
+            .with_find_window_result(move |_| Ok(Some(window_id)))
+            .with_take_screen_shot_result(|| Ok(image::RgbaImage::new(1, 1)))
+    }
+
     pub fn with_find_window_result<F>(mut self, result: F) -> Self
-        where F: Fn() -> anyhow::Result<Option<u64>> + 'static {
+        where F: Fn(&WindowSelector) -> anyhow::Result<Option<u64>> + 'static {
         self.find_window_result = Box::new(result);
         self
     }
+    pub fn with_find_windows_result<F>(mut self, result: F) -> Self
+        where F: Fn(&WindowSelector) -> anyhow::Result<Vec<WindowInfo>> + 'static {
+        self.find_windows_result = Box::new(result);
+        self
+    }
+    pub fn with_get_active_window_result<F>(mut self, result: F) -> Self
+        where F: Fn() -> anyhow::Result<Option<u64>> + 'static {
+        self.get_active_window_result = Box::new(result);
+        self
+    }
+    pub fn with_pick_window_result<F>(mut self, result: F) -> Self
+        where F: Fn() -> anyhow::Result<Option<u64>> + 'static {
+        self.pick_window_result = Box::new(result);
+        self
+    }
+    pub fn with_get_root_window_result<F>(mut self, result: F) -> Self
+        where F: Fn(Option<i32>) -> anyhow::Result<u64> + 'static {
+        self.get_root_window_result = Box::new(result);
+        self
+    }
+    pub fn with_get_frame_window_result<F>(mut self, result: F) -> Self
+        where F: Fn(u64) -> anyhow::Result<u64> + 'static {
+        self.get_frame_window_result = Box::new(result);
+        self
+    }
+    pub fn with_window_exists_result<F>(mut self, result: F) -> Self
+        where F: Fn(u64) -> anyhow::Result<bool> + 'static {
+        self.window_exists_result = Box::new(result);
+        self
+    }
+    pub fn with_get_window_info_result<F>(mut self, result: F) -> Self
+        where F: Fn(u64) -> anyhow::Result<WindowInfo> + 'static {
+        self.get_window_info_result = Box::new(result);
+        self
+    }
+    pub fn with_is_window_mapped_result<F>(mut self, result: F) -> Self
+        where F: Fn(u64) -> anyhow::Result<bool> + 'static {
+        self.is_window_mapped_result = Box::new(result);
+        self
+    }
     pub fn with_take_screen_shot_result<F>(mut self, result: F) -> Self
-        where F: Fn() -> anyhow::Result<image::RgbImage> + 'static {
+        where F: Fn() -> anyhow::Result<image::RgbaImage> + 'static {
         self.take_screen_shot_result = Box::new(result);
         self
     }
 
+    pub fn with_get_cursor_result<F>(mut self, result: F) -> Self
+        where F: Fn(u64) -> anyhow::Result<Cursor> + 'static {
+        self.get_cursor_result = Box::new(result);
+        self
+    }
+
+    pub fn with_wait_for_damage_result<F>(mut self, result: F) -> Self
+        where F: Fn(u64, Duration) -> anyhow::Result<bool> + 'static {
+        self.wait_for_damage_result = Box::new(result);
+        self
+    }
+
+    /// Records the region given to the last `take_screen_shot` call.
+    pub fn with_take_screen_shot_region_spy(mut self, spy: Rc<RefCell<Option<Region>>>) -> Self {
+        self.take_screen_shot_region_spy = spy;
+        self
+    }
+
+    /// Records the window given to the last `take_screen_shot` call.
+    pub fn with_take_screen_shot_window_spy(mut self, spy: Rc<Cell<Option<u64>>>) -> Self {
+        self.take_screen_shot_window_spy = spy;
+        self
+    }
+
     pub fn with_list_windows_result<F>(mut self, result: F) -> Self
-        where F: Fn() -> anyhow::Result<Vec<String>> + 'static  {
+        where F: Fn() -> anyhow::Result<Vec<WindowInfo>> + 'static  {
         self.list_windows_result = Box::new(result);
         self
     }
+
+    pub fn with_list_monitors_result<F>(mut self, result: F) -> Self
+        where F: Fn() -> anyhow::Result<Vec<MonitorInfo>> + 'static  {
+        self.list_monitors_result = Box::new(result);
+        self
+    }
+}
+
+impl Default for FakeWindowSystemAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ScreenShotWindowSystemGateway for FakeWindowSystemAdapter {
-    fn find_window(&self, _searched_window_name: &String) -> anyhow::Result<Option<u64>> {
-        (self.find_window_result)()
+    fn find_window(&self, selector: &WindowSelector) -> anyhow::Result<Option<u64>> {
+        (self.find_window_result)(selector)
     }
-    fn take_screen_shot(&self, _window_id: u64) -> anyhow::Result<image::RgbImage> {
+    fn find_windows(&self, selector: &WindowSelector) -> anyhow::Result<Vec<WindowInfo>> {
+        (self.find_windows_result)(selector)
+    }
+    fn get_active_window(&self) -> anyhow::Result<Option<u64>> {
+        (self.get_active_window_result)()
+    }
+    fn pick_window(&self) -> anyhow::Result<Option<u64>> {
+        (self.pick_window_result)()
+    }
+    fn get_root_window(&self, screen: Option<i32>) -> anyhow::Result<u64> {
+        (self.get_root_window_result)(screen)
+    }
+    fn get_frame_window(&self, window_id: u64) -> anyhow::Result<u64> {
+        (self.get_frame_window_result)(window_id)
+    }
+    fn window_exists(&self, window_id: u64) -> anyhow::Result<bool> {
+        (self.window_exists_result)(window_id)
+    }
+    fn get_window_info(&self, window_id: u64) -> anyhow::Result<WindowInfo> {
+        (self.get_window_info_result)(window_id)
+    }
+    fn is_window_mapped(&self, window_id: u64) -> anyhow::Result<bool> {
+        (self.is_window_mapped_result)(window_id)
+    }
+    fn take_screen_shot(&self, window_id: u64, region: Option<&Region>) -> anyhow::Result<image::RgbaImage> {
+        self.take_screen_shot_window_spy.set(Some(window_id));
+        *self.take_screen_shot_region_spy.borrow_mut() = region.copied();
         (self.take_screen_shot_result)()
     }
+    fn get_cursor(&self, window_id: u64) -> anyhow::Result<Cursor> {
+        (self.get_cursor_result)(window_id)
+    }
+    fn wait_for_damage(&self, window_id: u64, timeout: Duration) -> anyhow::Result<bool> {
+        (self.wait_for_damage_result)(window_id, timeout)
+    }
 }
 
 impl ListWindowsWindowSystemGateway for FakeWindowSystemAdapter {
-    fn list_windows(&self) -> anyhow::Result<Vec<String>> {
+    fn list_windows(&self) -> anyhow::Result<Vec<WindowInfo>> {
         (self.list_windows_result)()
     }
+}
+
+impl ListMonitorsWindowSystemGateway for FakeWindowSystemAdapter {
+    fn list_monitors(&self) -> anyhow::Result<Vec<MonitorInfo>> {
+        (self.list_monitors_result)()
+    }
 }
\ No newline at end of file
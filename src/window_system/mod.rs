@@ -1,2 +1,8 @@
 pub mod x11_dl_window_system_adapter;
-pub mod fake_window_system_adapter;
\ No newline at end of file
+pub mod fake_window_system_adapter;
+pub mod pixel_format;
+pub mod process_tree;
+pub(crate) mod shm_capture;
+pub(crate) mod composite_capture;
+pub(crate) mod damage_watch;
+pub(crate) mod x_errors;
\ No newline at end of file
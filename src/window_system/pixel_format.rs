@@ -0,0 +1,338 @@
+/// Position and width of a color channel within a pixel value, derived from its mask
+/// (e.g. `0xf800` for the red channel of RGB565 is 5 bits shifted by 11).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelMask {
+    shift: u32,
+    bits: u32,
+}
+
+impl ChannelMask {
+    pub fn from_mask(mask: u64) -> ChannelMask {
+        if mask == 0 {
+            return ChannelMask { shift: 0, bits: 0 };
+        }
+        let shift = mask.trailing_zeros();
+        ChannelMask {
+            shift,
+            bits: (mask >> shift).trailing_ones(),
+        }
+    }
+
+    /// Extracts the channel from the pixel, scaled to 8 bits.
+    pub fn extract(&self, pixel: u64) -> u8 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let max = (1u64 << self.bits) - 1;
+        let value = (pixel >> self.shift) & max;
+        if self.bits >= 8 {
+            (value >> (self.bits - 8)) as u8
+        } else {
+            // Rounds to the nearest value, so that the maximum maps to 255
+            ((value * 255 + max / 2) / max) as u8
+        }
+    }
+}
+
+/// Describes how to convert the pixel values of an XImage to colors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// TrueColor and DirectColor visuals: the pixel values hold the channels.
+    Masks {
+        red: ChannelMask,
+        green: ChannelMask,
+        blue: ChannelMask,
+        alpha: Option<ChannelMask>,
+    },
+    /// PseudoColor, StaticColor and grayscale visuals: the pixel values are indexes of the colormap.
+    Colormap(Vec<image::Rgba<u8>>),
+}
+
+impl PixelFormat {
+    /// The alpha channel is only meaningful for 32 bits deep (ARGB) visuals, where it is stored in the bits
+    /// that are not used by the color channels.
+    pub fn from_masks(red_mask: u64, green_mask: u64, blue_mask: u64, depth: u32) -> PixelFormat {
+        let alpha_mask = if depth == 32 {
+            0xffff_ffff & !(red_mask | green_mask | blue_mask)
+        } else {
+            0
+        };
+        PixelFormat::Masks {
+            red: ChannelMask::from_mask(red_mask),
+            green: ChannelMask::from_mask(green_mask),
+            blue: ChannelMask::from_mask(blue_mask),
+            alpha: (alpha_mask != 0).then(|| ChannelMask::from_mask(alpha_mask)),
+        }
+    }
+
+    /// Converts a whole pixel buffer at once, or returns `None` if its layout is not supported.
+    pub fn convert(&self, buffer: &PixelBuffer) -> Option<image::RgbaImage> {
+        if !buffer.is_supported() {
+            return None;
+        }
+        let mut result = image::RgbaImage::new(buffer.width, buffer.height);
+        let byte_offsets = self.byte_offsets(buffer);
+        for (y, row) in result.rows_mut().enumerate() {
+            let line = &buffer.data[y * buffer.bytes_per_line..];
+            for (x, output) in row.enumerate() {
+                *output = match byte_offsets {
+                    Some((r, g, b, a)) => {
+                        let pixel = &line[x * 4..x * 4 + 4];
+                        unpremultiply(image::Rgba([pixel[r], pixel[g], pixel[b], a.map_or(u8::MAX, |a| pixel[a])]))
+                    }
+                    None => self.to_rgba(buffer.read_pixel(line, x)),
+                };
+            }
+        }
+        Some(result)
+    }
+
+    // When every channel fills a whole byte of a 32 bits pixel, they can be copied without any bit twiddling
+    fn byte_offsets(&self, buffer: &PixelBuffer) -> Option<(usize, usize, usize, Option<usize>)> {
+        let PixelFormat::Masks { red, green, blue, alpha } = self else {
+            return None;
+        };
+        if buffer.bits_per_pixel != 32 {
+            return None;
+        }
+        let byte_offset = |channel: &ChannelMask| {
+            if channel.bits != 8 || channel.shift & 7 != 0 {
+                return None;
+            }
+            let index = (channel.shift / 8) as usize;
+            Some(match buffer.byte_order {
+                ByteOrder::LsbFirst => index,
+                ByteOrder::MsbFirst => 3 - index,
+            })
+        };
+        let alpha = match alpha {
+            Some(alpha) => Some(byte_offset(alpha)?),
+            None => None,
+        };
+        Some((byte_offset(red)?, byte_offset(green)?, byte_offset(blue)?, alpha))
+    }
+
+    pub fn to_rgba(&self, pixel: u64) -> image::Rgba<u8> {
+        match self {
+            PixelFormat::Masks { red, green, blue, alpha } => unpremultiply(image::Rgba([
+                red.extract(pixel),
+                green.extract(pixel),
+                blue.extract(pixel),
+                alpha.map_or(u8::MAX, |alpha| alpha.extract(pixel)),
+            ])),
+            PixelFormat::Colormap(colors) => colors
+                .get(pixel as usize)
+                .copied()
+                .unwrap_or(image::Rgba([0, 0, 0, u8::MAX])),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    LsbFirst,
+    MsbFirst,
+}
+
+/// The raw data of a ZPixmap image, as laid out by the X server.
+pub struct PixelBuffer<'a> {
+    pub data: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+    pub bytes_per_line: usize,
+    pub bits_per_pixel: u32,
+    pub byte_order: ByteOrder,
+}
+
+impl PixelBuffer<'_> {
+    fn is_supported(&self) -> bool {
+        matches!(self.bits_per_pixel, 8 | 16 | 24 | 32)
+            && self.bytes_per_line >= self.width as usize * self.bits_per_pixel as usize / 8
+            && self.data.len() >= self.bytes_per_line * self.height as usize
+    }
+
+    fn read_pixel(&self, line: &[u8], x: usize) -> u64 {
+        let bytes_per_pixel = self.bits_per_pixel as usize / 8;
+        let bytes = &line[x * bytes_per_pixel..(x + 1) * bytes_per_pixel];
+        match self.byte_order {
+            ByteOrder::LsbFirst => bytes.iter().rev().fold(0, |pixel, &byte| pixel << 8 | byte as u64),
+            ByteOrder::MsbFirst => bytes.iter().fold(0, |pixel, &byte| pixel << 8 | byte as u64),
+        }
+    }
+}
+
+// The RENDER extension (and thus ARGB visuals) uses premultiplied alpha, whereas image files don't.
+fn unpremultiply(pixel: image::Rgba<u8>) -> image::Rgba<u8> {
+    let image::Rgba([r, g, b, a]) = pixel;
+    if a == u8::MAX || a == 0 {
+        return pixel;
+    }
+    let unpremultiply_channel = |channel: u8| (channel as u32 * 255 / a as u32).min(255) as u8;
+    image::Rgba([unpremultiply_channel(r), unpremultiply_channel(g), unpremultiply_channel(b), a])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::window_system::pixel_format::{ByteOrder, ChannelMask, PixelBuffer, PixelFormat};
+
+    #[test]
+    fn it_should_derive_the_channel_position_from_the_mask() {
+        assert_eq!(ChannelMask::from_mask(0xff0000), ChannelMask { shift: 16, bits: 8 });
+        assert_eq!(ChannelMask::from_mask(0xf800), ChannelMask { shift: 11, bits: 5 });
+        assert_eq!(ChannelMask::from_mask(0x3ff00000), ChannelMask { shift: 20, bits: 10 });
+        assert_eq!(ChannelMask::from_mask(0), ChannelMask { shift: 0, bits: 0 });
+    }
+
+    #[test]
+    fn it_should_convert_24_bits_pixels() {
+        // Given
+        let format = PixelFormat::from_masks(0xff0000, 0x00ff00, 0x0000ff, 24);
+
+        // Then
+        assert_eq!(format.to_rgba(0x102030), image::Rgba([0x10, 0x20, 0x30, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_bgr_pixels() {
+        // Given
+        let format = PixelFormat::from_masks(0x0000ff, 0x00ff00, 0xff0000, 24);
+
+        // Then
+        assert_eq!(format.to_rgba(0x102030), image::Rgba([0x30, 0x20, 0x10, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_rgb565_pixels() {
+        // Given
+        let format = PixelFormat::from_masks(0xf800, 0x07e0, 0x001f, 16);
+
+        // Then
+        assert_eq!(format.to_rgba(0xffff), image::Rgba([0xff, 0xff, 0xff, 0xff]));
+        assert_eq!(format.to_rgba(0xf800), image::Rgba([0xff, 0x00, 0x00, 0xff]));
+        assert_eq!(format.to_rgba(0x0400), image::Rgba([0x00, 0x82, 0x00, 0xff]));
+        assert_eq!(format.to_rgba(0x0010), image::Rgba([0x00, 0x00, 0x84, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_30_bits_pixels() {
+        // Given
+        let format = PixelFormat::from_masks(0x3ff00000, 0x000ffc00, 0x000003ff, 30);
+
+        // Then
+        assert_eq!(format.to_rgba(0x3ff00000), image::Rgba([0xff, 0x00, 0x00, 0xff]));
+        assert_eq!(format.to_rgba(0x00080200), image::Rgba([0x00, 0x80, 0x80, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_argb_pixels() {
+        // Given
+        let format = PixelFormat::from_masks(0xff0000, 0x00ff00, 0x0000ff, 32);
+
+        // Then
+        assert_eq!(format.to_rgba(0xff102030), image::Rgba([0x10, 0x20, 0x30, 0xff]));
+        assert_eq!(format.to_rgba(0x00000000), image::Rgba([0x00, 0x00, 0x00, 0x00]));
+        // Premultiplied alpha
+        assert_eq!(format.to_rgba(0x80400000), image::Rgba([0x7f, 0x00, 0x00, 0x80]));
+    }
+
+    #[test]
+    fn it_should_convert_indexed_pixels_through_the_colormap() {
+        // Given
+        let format = PixelFormat::Colormap(vec![
+            image::Rgba([0, 0, 0, 0xff]),
+            image::Rgba([0xff, 0x80, 0, 0xff]),
+        ]);
+
+        // Then
+        assert_eq!(format.to_rgba(1), image::Rgba([0xff, 0x80, 0, 0xff]));
+        assert_eq!(format.to_rgba(42), image::Rgba([0, 0, 0, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_32_bits_buffers_with_padded_lines() {
+        // Given
+        let format = PixelFormat::from_masks(0xff0000, 0x00ff00, 0x0000ff, 24);
+        let data = [
+            0x30, 0x20, 0x10, 0x00, 0x03, 0x02, 0x01, 0x00, 0xee, 0xee, 0xee, 0xee,
+            0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0xee, 0xee, 0xee, 0xee,
+        ];
+        let buffer = PixelBuffer {
+            data: &data,
+            width: 2,
+            height: 2,
+            bytes_per_line: 12,
+            bits_per_pixel: 32,
+            byte_order: ByteOrder::LsbFirst,
+        };
+
+        // When
+        let result = format.convert(&buffer).unwrap();
+
+        // Then
+        assert_eq!(result.get_pixel(0, 0), &image::Rgba([0x10, 0x20, 0x30, 0xff]));
+        assert_eq!(result.get_pixel(1, 0), &image::Rgba([0x01, 0x02, 0x03, 0xff]));
+        assert_eq!(result.get_pixel(0, 1), &image::Rgba([0xff, 0xff, 0xff, 0xff]));
+        assert_eq!(result.get_pixel(1, 1), &image::Rgba([0x00, 0x00, 0x00, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_big_endian_argb_buffers() {
+        // Given
+        let format = PixelFormat::from_masks(0xff0000, 0x00ff00, 0x0000ff, 32);
+        let data = [0xff, 0x10, 0x20, 0x30];
+        let buffer = PixelBuffer {
+            data: &data,
+            width: 1,
+            height: 1,
+            bytes_per_line: 4,
+            bits_per_pixel: 32,
+            byte_order: ByteOrder::MsbFirst,
+        };
+
+        // When
+        let result = format.convert(&buffer).unwrap();
+
+        // Then
+        assert_eq!(result.get_pixel(0, 0), &image::Rgba([0x10, 0x20, 0x30, 0xff]));
+    }
+
+    #[test]
+    fn it_should_convert_16_bits_buffers() {
+        // Given
+        let format = PixelFormat::from_masks(0xf800, 0x07e0, 0x001f, 16);
+        let data = [0x00, 0xf8, 0x1f, 0x00];
+        let buffer = PixelBuffer {
+            data: &data,
+            width: 2,
+            height: 1,
+            bytes_per_line: 4,
+            bits_per_pixel: 16,
+            byte_order: ByteOrder::LsbFirst,
+        };
+
+        // When
+        let result = format.convert(&buffer).unwrap();
+
+        // Then
+        assert_eq!(result.get_pixel(0, 0), &image::Rgba([0xff, 0x00, 0x00, 0xff]));
+        assert_eq!(result.get_pixel(1, 0), &image::Rgba([0x00, 0x00, 0xff, 0xff]));
+    }
+
+    #[test]
+    fn it_should_not_convert_unsupported_buffers() {
+        // Given
+        let format = PixelFormat::from_masks(0xff0000, 0x00ff00, 0x0000ff, 24);
+        let data = [0x00; 4];
+        let buffer = PixelBuffer {
+            data: &data,
+            width: 8,
+            height: 1,
+            bytes_per_line: 4,
+            bits_per_pixel: 4,
+            byte_order: ByteOrder::LsbFirst,
+        };
+
+        // Then
+        assert_eq!(format.convert(&buffer), None);
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+/// Lists the processes descending from `pid` (children, grand-children, ...), based on the parent
+/// process ids exposed by `/proc/<pid>/stat`.
+pub fn find_descendant_processes(pid: u32) -> anyhow::Result<Vec<u32>> {
+    let entries = std::fs::read_dir("/proc")
+        .map_err(|e| anyhow::anyhow!("Unable to list the running processes: {}", e))?;
+    let parents: Vec<(u32, u32)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|child| {
+            // The process may have exited in the meantime
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", child)).ok()?;
+            Some((child, parse_parent_pid(&stat)?))
+        })
+        .collect();
+    Ok(collect_descendants(pid, &parents))
+}
+
+// https://man7.org/linux/man-pages/man5/proc_pid_stat.5.html
+// The command name is between parentheses and may itself contain spaces and parentheses, hence
+// the fields are read from the last closing parenthesis.
+fn parse_parent_pid(stat: &str) -> Option<u32> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn collect_descendants(pid: u32, parents: &[(u32, u32)]) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(child, parent) in parents {
+        children.entry(parent).or_default().push(child);
+    }
+
+    let mut result = Vec::new();
+    let mut to_visit = vec![pid];
+    while let Some(current) = to_visit.pop() {
+        for &child in children.get(&current).into_iter().flatten() {
+            if child != pid && !result.contains(&child) {
+                result.push(child);
+                to_visit.push(child);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::window_system::process_tree::{collect_descendants, parse_parent_pid};
+
+    #[test]
+    fn it_should_parse_the_parent_pid() {
+        assert_eq!(parse_parent_pid("1234 (bash) S 1000 1234 1234 34816"), Some(1000));
+        assert_eq!(parse_parent_pid("1234 (my (weird) app) R 42 1234 1234 0"), Some(42));
+        assert_eq!(parse_parent_pid("garbage"), None);
+    }
+
+    #[test]
+    fn it_should_collect_all_the_descendants() {
+        // Given
+        let parents = vec![(10, 1), (11, 10), (12, 10), (13, 12), (20, 1), (21, 20)];
+
+        // When
+        let mut descendants = collect_descendants(10, &parents);
+        descendants.sort();
+
+        // Then
+        assert_eq!(descendants, vec![11, 12, 13]);
+    }
+
+    #[test]
+    fn it_should_yield_nothing_for_a_process_without_children() {
+        assert!(collect_descendants(13, &[(10, 1), (13, 10)]).is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,135 @@
+use std::rc::Rc;
+
+use x11_dl::xlib::{self, Xlib};
+use x11_dl::xshm::{Xext, XShmSegmentInfo};
+
+use crate::region::Region;
+use crate::window_system::x_errors::trap_x_errors;
+
+/// Captures drawables through a shared memory segment (MIT-SHM extension), so that the pixels are not
+/// copied over the X connection. The segment is kept, and reused by the next captures as long as it is
+/// large enough.
+pub struct ShmCapture {
+    xlib: Rc<Xlib>,
+    xext: Xext,
+    display: *mut xlib::Display,
+    segment: Option<XShmSegmentInfo>,
+    segment_size: usize,
+}
+
+impl ShmCapture {
+    /// Returns `None` if the Xext library cannot be loaded, or if the X server does not support MIT-SHM.
+    pub fn new(xlib: Rc<Xlib>, display: *mut xlib::Display) -> Option<ShmCapture> {
+        let xext = Xext::open().ok()?;
+        unsafe {
+            if (xext.XShmQueryExtension)(display) == 0 {
+                return None;
+            }
+        }
+        Some(ShmCapture {
+            xlib,
+            xext,
+            display,
+            segment: None,
+            segment_size: 0,
+        })
+    }
+
+    /// Gets the pixels of the given region of the drawable, and hands the resulting ZPixmap image to `convert`
+    /// (the image is only valid during the call).
+    /// Returns `None` if this capture failed (e.g. the window has been unmapped meanwhile), and an error if no
+    /// capture can go through MIT-SHM, e.g. when the X server cannot attach the segment because it runs on another
+    /// host.
+    pub fn capture<T, F>(
+        &mut self,
+        drawable: xlib::Drawable,
+        attributes: &xlib::XWindowAttributes,
+        region: &Region,
+        convert: F,
+    ) -> anyhow::Result<Option<T>> where
+        F: FnOnce(*mut xlib::XImage) -> T,
+    {
+        unsafe {
+            let image = (self.xext.XShmCreateImage)(
+                self.display,
+                attributes.visual,
+                attributes.depth as _,
+                xlib::ZPixmap,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                region.width,
+                region.height,
+            );
+            if image.is_null() {
+                return Ok(None);
+            }
+            let size = (*image).bytes_per_line as usize * region.height as usize;
+            let result = self.get_segment(size).map(|mut segment| {
+                (*image).data = segment.shmaddr;
+                (*image).obdata = &mut segment as *mut XShmSegmentInfo as _;
+                let (status, failed) = trap_x_errors(&self.xlib, self.display, || {
+                    (self.xext.XShmGetImage)(self.display, drawable, image, region.x as _, region.y as _, (self.xlib.XAllPlanes)() as _)
+                });
+                (status != 0 && !failed).then(|| convert(image))
+            });
+            // The data belongs to the segment, it must not be freed along with the image
+            (*image).data = std::ptr::null_mut();
+            (*image).obdata = std::ptr::null_mut();
+            (self.xlib.XDestroyImage)(image);
+            result
+        }
+    }
+
+    fn get_segment(&mut self, size: usize) -> anyhow::Result<XShmSegmentInfo> {
+        if let Some(segment) = self.segment.filter(|_| size <= self.segment_size) {
+            return Ok(segment);
+        }
+        self.release_segment();
+        unsafe {
+            let shmid = libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600);
+            if shmid < 0 {
+                anyhow::bail!("Unable to create a shared memory segment: {}", std::io::Error::last_os_error());
+            }
+            let shmaddr = libc::shmat(shmid, std::ptr::null(), 0);
+            let attach_error = std::io::Error::last_os_error();
+            // The segment is destroyed once both the X server and this process have detached it
+            libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut());
+            if shmaddr as isize == -1 {
+                anyhow::bail!("Unable to attach the shared memory segment: {}", attach_error);
+            }
+            let mut segment = XShmSegmentInfo {
+                shmseg: 0,
+                shmid,
+                shmaddr: shmaddr as _,
+                readOnly: xlib::False,
+            };
+            let (status, failed) = trap_x_errors(&self.xlib, self.display, || {
+                (self.xext.XShmAttach)(self.display, &mut segment)
+            });
+            if status == 0 || failed {
+                libc::shmdt(shmaddr);
+                anyhow::bail!("The X server is unable to attach the shared memory segment");
+            }
+            self.segment = Some(segment);
+            self.segment_size = size;
+            Ok(segment)
+        }
+    }
+
+    fn release_segment(&mut self) {
+        if let Some(mut segment) = self.segment.take() {
+            unsafe {
+                (self.xext.XShmDetach)(self.display, &mut segment);
+                (self.xlib.XSync)(self.display, xlib::False);
+                libc::shmdt(segment.shmaddr as _);
+            }
+        }
+        self.segment_size = 0;
+    }
+}
+
+impl Drop for ShmCapture {
+    fn drop(&mut self) {
+        self.release_segment();
+    }
+}
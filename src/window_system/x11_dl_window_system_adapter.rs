@@ -1,70 +1,148 @@
-use std::ffi::CStr;
-use crate::gateways::{ListWindowsWindowSystemGateway, ScreenShotWindowSystemGateway};
+use std::cell::{OnceCell, RefCell};
+use std::ffi::{CStr, CString};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use x11_dl::xlib::{self, Xlib};
+use crate::cursor::Cursor;
+use crate::gateways::{ListMonitorsWindowSystemGateway, ListWindowsWindowSystemGateway, ScreenShotWindowSystemGateway};
+use crate::region::Region;
+use crate::results::{MonitorInfo, WindowInfo};
+use crate::window_selector::WindowSelector;
+use crate::window_system::composite_capture::CompositeCapture;
+use crate::window_system::damage_watch::DamageWatch;
+use crate::window_system::pixel_format::{ByteOrder, PixelBuffer, PixelFormat};
+use crate::window_system::shm_capture::ShmCapture;
+use crate::window_system::process_tree::find_descendant_processes;
 
+// https://gitlab.freedesktop.org/xorg/proto/xorgproto/-/blob/master/include/X11/cursorfont.h
+const XC_CROSSHAIR: u32 = 34;
+
+// How long a window redirected off-screen is given to start repainting, and then to complete its repaint
+const REPAINT_TIMEOUT: Duration = Duration::from_millis(500);
+const REPAINT_QUIET_PERIOD: Duration = Duration::from_millis(20);
+
+// Every library is loaded at runtime: libX11 and the extensions x11-dl has bindings for through it, the other
+// extensions through libloading. The extensions are only queried once needed, so that e.g. listing the windows
+// does not depend on them.
 pub struct X11DLWindowSystemAdapter {
-    display: *mut x11::xlib::Display,
-    root_win: x11::xlib::Window,
+    xlib: Rc<Xlib>,
+    display: *mut xlib::Display,
+    root_win: xlib::Window,
+    offscreen: bool,
+    shm_capture: OnceCell<RefCell<Option<ShmCapture>>>,
+    composite_capture: OnceCell<Option<CompositeCapture>>,
+    damage_watch: OnceCell<Option<DamageWatch>>,
 }
 
 impl X11DLWindowSystemAdapter {
     pub fn new() -> anyhow::Result<X11DLWindowSystemAdapter> {
+        let xlib = Xlib::open().map_err(|e| anyhow::anyhow!("Unable to load the Xlib library: {}", e))?;
         unsafe {
-            let display = x11::xlib::XOpenDisplay(std::ptr::null());
+            let display = (xlib.XOpenDisplay)(std::ptr::null());
             if display.is_null() {
                 anyhow::bail!("Unable to open X server display")
             }
-            let root_win = x11::xlib::XDefaultRootWindow(display);
-            Ok(X11DLWindowSystemAdapter { display, root_win })
+            let root_win = (xlib.XDefaultRootWindow)(display);
+            Ok(X11DLWindowSystemAdapter {
+                xlib: Rc::new(xlib),
+                display,
+                root_win,
+                offscreen: false,
+                shm_capture: OnceCell::new(),
+                composite_capture: OnceCell::new(),
+                damage_watch: OnceCell::new(),
+            })
         }
     }
 
-    fn find_window_recursive_helper(
+    /// Captures the windows from their off-screen contents (Composite extension), so that the windows obscured by
+    /// other windows or partially off-screen are captured as they would look like if they were fully visible.
+    /// Each window is only redirected off-screen for the time of its capture, which makes it repaint.
+    pub fn with_offscreen_capture(mut self, offscreen: bool) -> Self {
+        self.offscreen = offscreen;
+        self
+    }
+
+    fn shm_capture(&self) -> &RefCell<Option<ShmCapture>> {
+        self.shm_capture.get_or_init(|| RefCell::new(ShmCapture::new(self.xlib.clone(), self.display)))
+    }
+
+    fn composite_capture(&self) -> Option<&CompositeCapture> {
+        self.composite_capture.get_or_init(|| CompositeCapture::new(self.xlib.clone(), self.display)).as_ref()
+    }
+
+    fn damage_watch(&self) -> Option<&DamageWatch> {
+        self.damage_watch.get_or_init(|| DamageWatch::new(self.xlib.clone(), self.display)).as_ref()
+    }
+
+    fn find_window_recursive_helper<P>(
         &self,
-        searched_window_name: &String,
-        window: x11::xlib::Window,
-    ) -> anyhow::Result<Option<x11::xlib::Window>> {
-        let title = self.get_window_title(window)?;
-        if title.is_some() && title.unwrap() == *searched_window_name {
+        predicate: &P,
+        window: xlib::Window,
+    ) -> anyhow::Result<Option<xlib::Window>> where
+        P: Fn(xlib::Window) -> anyhow::Result<bool>,
+    {
+        if predicate(window)? {
             return Ok(Some(window));
         }
-        Ok(self.iterate_over_window_childrens(
+        self.iterate_over_window_childrens(
             window,
-            |child_window| self.find_window_recursive_helper(searched_window_name, child_window),
-        )?)
+            |child_window| self.find_window_recursive_helper(predicate, child_window),
+        )
     }
 
-    fn list_windows_recursive_helper(
+    fn list_windows_recursive_helper<P>(
         &self,
-        window: x11::xlib::Window,
-        result: &mut Vec<String>,
-    ) -> anyhow::Result<Option<()>> {
-        let title = self.get_window_title(window)?;
-        if title.is_some() {
-            result.push(title.unwrap());
+        predicate: &P,
+        window: xlib::Window,
+        result: &mut Vec<WindowInfo>,
+    ) -> anyhow::Result<Option<()>> where
+        P: Fn(&WindowInfo) -> bool,
+    {
+        let info = self.get_window_info(window)?;
+        if predicate(&info) {
+            result.push(info);
         }
 
         self.iterate_over_window_childrens(
             window,
-            |child_window| self.list_windows_recursive_helper(child_window, result),
+            |child_window| self.list_windows_recursive_helper(predicate, child_window, result),
         )?;
 
         Ok(None)
     }
 
+    fn get_parent_window(&self, window: xlib::Window) -> anyhow::Result<xlib::Window> {
+        unsafe {
+            let mut root_return: xlib::Window = 0;
+            let mut parent_return: xlib::Window = 0;
+            let mut children: *mut xlib::Window = std::ptr::null_mut();
+            let mut nchildren: u32 = 0;
+
+            if (self.xlib.XQueryTree)(self.display, window, &mut root_return, &mut parent_return, &mut children, &mut nchildren) == 0 {
+                anyhow::bail!("Unable to query the window tree for window {:#x}", window);
+            }
+            if !children.is_null() {
+                (self.xlib.XFree)(children as *mut _);
+            }
+            Ok(parent_return)
+        }
+    }
+
     fn iterate_over_window_childrens<T, F>(
         &self,
-        window: x11::xlib::Window,
+        window: xlib::Window,
         mut fun: F,
     ) -> anyhow::Result<Option<T>> where
-        F: FnMut(x11::xlib::Window) -> anyhow::Result<Option<T>>,
+        F: FnMut(xlib::Window) -> anyhow::Result<Option<T>>,
     {
         unsafe {
-            let mut root_return: x11::xlib::Window = 0;
-            let mut parent_return: x11::xlib::Window = 0;
-            let mut children: *mut x11::xlib::Window = std::ptr::null_mut();
+            let mut root_return: xlib::Window = 0;
+            let mut parent_return: xlib::Window = 0;
+            let mut children: *mut xlib::Window = std::ptr::null_mut();
             let mut nchildren: u32 = 0;
 
-            if !x11::xlib::XQueryTree(self.display, window, &mut root_return, &mut parent_return, &mut children, &mut nchildren) == 0 {
+            if !(self.xlib.XQueryTree)(self.display, window, &mut root_return, &mut parent_return, &mut children, &mut nchildren) == 0 {
                 anyhow::bail!("Unable to query the root window tree for window {:x}", window);
             }
             if children.is_null() {
@@ -82,16 +160,370 @@ impl X11DLWindowSystemAdapter {
 
             // Free the memory allocated for child windows
             if !children.is_null() {
-                x11::xlib::XFree(children as *mut _);
+                (self.xlib.XFree)(children as *mut _);
             }
 
             Ok(None)
         }
     }
 
+    // According to Select_Window in https://gitlab.freedesktop.org/xorg/app/xwininfo/-/blob/master/dsimple.c
+    // The pointer is grabbed on the root window, so the click is not forwarded to the clicked application.
+    // Returns the top-level window under the pointer (usually the window manager frame), or None if the
+    // selection has been cancelled with another button than the left one.
+    fn wait_for_window_click(&self) -> anyhow::Result<Option<xlib::Window>> {
+        unsafe {
+            let cursor = (self.xlib.XCreateFontCursor)(self.display, XC_CROSSHAIR);
+            let event_mask = xlib::ButtonPressMask | xlib::ButtonReleaseMask;
+            let status = (self.xlib.XGrabPointer)(
+                self.display,
+                self.root_win,
+                xlib::False,
+                event_mask as _,
+                xlib::GrabModeSync,
+                xlib::GrabModeAsync,
+                self.root_win,
+                cursor,
+                xlib::CurrentTime,
+            );
+            if status != xlib::GrabSuccess {
+                (self.xlib.XFreeCursor)(self.display, cursor);
+                anyhow::bail!("Unable to grab the pointer to pick a window");
+            }
+
+            let mut clicked_window = None;
+            let mut cancelled = false;
+            let mut pressed_buttons: u32 = 0;
+            // Waits for all the buttons to be released, so the release is not received by the application
+            while (clicked_window.is_none() && !cancelled) || pressed_buttons > 0 {
+                (self.xlib.XAllowEvents)(self.display, xlib::SyncPointer, xlib::CurrentTime);
+                let mut event: xlib::XEvent = std::mem::zeroed();
+                (self.xlib.XWindowEvent)(self.display, self.root_win, event_mask, &mut event);
+                match event.get_type() {
+                    xlib::ButtonPress => {
+                        if clicked_window.is_none() && !cancelled {
+                            if event.button.button == xlib::Button1 {
+                                let subwindow = event.button.subwindow;
+                                clicked_window = Some(if subwindow == 0 { self.root_win } else { subwindow });
+                            } else {
+                                cancelled = true;
+                            }
+                        }
+                        pressed_buttons += 1;
+                    }
+                    xlib::ButtonRelease => {
+                        pressed_buttons = pressed_buttons.saturating_sub(1);
+                    }
+                    _ => {}
+                }
+            }
+
+            (self.xlib.XUngrabPointer)(self.display, xlib::CurrentTime);
+            (self.xlib.XFreeCursor)(self.display, cursor);
+            Ok(clicked_window)
+        }
+    }
+
+    // According to XmuClientWindow: window managers reparent the client windows into frames, the
+    // client window being the one holding the WM_STATE property.
+    fn find_client_window(&self, window: xlib::Window) -> anyhow::Result<xlib::Window> {
+        if window == self.root_win {
+            return Ok(window);
+        }
+        let client_window = self.find_window_recursive_helper(
+            &|window| Ok(self.has_window_property(window, c"WM_STATE")),
+            window,
+        )?;
+        Ok(client_window.unwrap_or(window))
+    }
+
+    fn has_window_property(&self, window: xlib::Window, property_name: &CStr) -> bool {
+        unsafe {
+            let property = (self.xlib.XInternAtom)(self.display, property_name.as_ptr(), xlib::True);
+            if property == 0 {
+                return false;
+            }
+
+            let mut actual_type: xlib::Atom = 0;
+            let mut actual_format: i32 = 0;
+            let mut nitems: u64 = 0;
+            let mut bytes_after: u64 = 0;
+            let mut data: *mut u8 = std::ptr::null_mut();
+            let ret = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                property,
+                0,
+                0,
+                xlib::False,
+                xlib::AnyPropertyType as _,
+                &mut actual_type,
+                &mut actual_format,
+                &mut nitems,
+                &mut bytes_after,
+                &mut data,
+            );
+            if !data.is_null() {
+                (self.xlib.XFree)(data as _);
+            }
+            ret == xlib::Success as i32 && actual_type != 0
+        }
+    }
+
+    // https://specifications.freedesktop.org/wm-spec/latest/ar01s05.html#id-1.6.14
+    fn get_window_pid(&self, window: xlib::Window) -> Option<u32> {
+        self.get_window_property_32(window, c"_NET_WM_PID", xlib::XA_CARDINAL)?
+            .first()
+            .map(|&pid| pid as u32)
+    }
+
+    // https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XGetWindowProperty
+    // Note that Xlib returns the items of 32 bits properties as C longs.
+    fn get_window_property_32(
+        &self,
+        window: xlib::Window,
+        property_name: &CStr,
+        property_type: xlib::Atom,
+    ) -> Option<Vec<u64>> {
+        unsafe {
+            let property = (self.xlib.XInternAtom)(self.display, property_name.as_ptr(), xlib::True);
+            if property == 0 {
+                return None;
+            }
+
+            let mut actual_type: xlib::Atom = 0;
+            let mut actual_format: i32 = 0;
+            let mut nitems: u64 = 0;
+            let mut bytes_after: u64 = 0;
+            let mut data: *mut u8 = std::ptr::null_mut();
+            let ret = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                property,
+                0,
+                i64::MAX,
+                xlib::False,
+                property_type,
+                &mut actual_type,
+                &mut actual_format,
+                &mut nitems,
+                &mut bytes_after,
+                &mut data,
+            );
+            if ret != xlib::Success as i32 || data.is_null() {
+                return None;
+            }
+
+            let value = if actual_type == property_type && actual_format == 32 {
+                Some(std::slice::from_raw_parts(data as *const u64, nitems as usize).to_vec())
+            } else {
+                None
+            };
+            (self.xlib.XFree)(data as _);
+            value
+        }
+    }
+
+    // The window pixmap includes the border of the window, unlike XGetImage on the window itself
+    fn take_screen_shot_with_composite(
+        &self,
+        window_id: u64,
+        attributes: &xlib::XWindowAttributes,
+        region: &Region,
+    ) -> anyhow::Result<image::RgbaImage> {
+        let Some(composite_capture) = self.composite_capture() else {
+            anyhow::bail!("The X server does not support the Composite extension");
+        };
+        let damage_watch = if self.is_compositing_manager_running(attributes) {
+            None
+        } else {
+            let Some(damage_watch) = self.damage_watch() else {
+                anyhow::bail!("The X server does not support the Damage extension");
+            };
+            // Only the changes made from the redirection on are part of the repaint
+            damage_watch.wait_for_damage(window_id, Duration::ZERO)?;
+            Some(damage_watch)
+        };
+        composite_capture.redirect_window(window_id)?;
+        let result = damage_watch
+            .map_or(Ok(()), |damage_watch| self.wait_for_repaint(damage_watch, window_id))
+            .and_then(|_| composite_capture.name_window_pixmap(window_id))
+            .and_then(|pixmap| {
+                let border_width = attributes.border_width as u32;
+                let pixmap_region = Region { x: region.x + border_width, y: region.y + border_width, ..*region };
+                let imgbuf = self.capture_drawable(pixmap, attributes, &pixmap_region);
+                unsafe {
+                    (self.xlib.XFreePixmap)(self.display, pixmap);
+                }
+                imgbuf
+            });
+        composite_capture.unredirect_window(window_id);
+        result
+    }
+
+    // A window which does not start repainting in time is captured as is
+    fn wait_for_repaint(&self, damage_watch: &DamageWatch, window_id: u64) -> anyhow::Result<()> {
+        let deadline = Instant::now() + REPAINT_TIMEOUT;
+        let mut timeout = REPAINT_TIMEOUT;
+        while damage_watch.wait_for_damage(window_id, timeout)? {
+            timeout = REPAINT_QUIET_PERIOD.min(deadline.saturating_duration_since(Instant::now()));
+            if timeout.is_zero() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // https://specifications.freedesktop.org/wm-spec/latest/ar01s08.html#id-1.9.7
+    // A compositing manager already keeps the contents of every window off-screen.
+    fn is_compositing_manager_running(&self, attributes: &xlib::XWindowAttributes) -> bool {
+        unsafe {
+            let screen = (self.xlib.XScreenNumberOfScreen)(attributes.screen);
+            let Ok(selection_name) = CString::new(format!("_NET_WM_CM_S{}", screen)) else {
+                return false;
+            };
+            let selection = (self.xlib.XInternAtom)(self.display, selection_name.as_ptr(), xlib::False);
+            (self.xlib.XGetSelectionOwner)(self.display, selection) != 0
+        }
+    }
+
+    fn capture_drawable(
+        &self,
+        drawable: xlib::Drawable,
+        attributes: &xlib::XWindowAttributes,
+        region: &Region,
+    ) -> anyhow::Result<image::RgbaImage> {
+        if let Some(imgbuf) = self.take_screen_shot_with_shm(drawable, attributes, region) {
+            return Ok(imgbuf);
+        }
+        unsafe {
+            let image = (self.xlib.XGetImage)(
+                self.display,
+                drawable,
+                region.x as _,
+                region.y as _,
+                region.width as _,
+                region.height as _,
+                (self.xlib.XAllPlanes)(),
+                xlib::ZPixmap as _,
+            );
+            if image.is_null() {
+                anyhow::bail!("Unable to get the pxiel data from window {:#x}", drawable);
+            }
+            let imgbuf = self.convert_image(attributes, image, region.width, region.height);
+            (self.xlib.XDestroyImage)(image);
+            Ok(imgbuf)
+        }
+    }
+
+    // A failed capture (e.g. of a window unmapped meanwhile) is retried with XGetImage, which reports the error.
+    // Once the segment cannot be attached at all (e.g. on a remote display), the next captures directly go through
+    // XGetImage.
+    fn take_screen_shot_with_shm(
+        &self,
+        drawable: xlib::Drawable,
+        attributes: &xlib::XWindowAttributes,
+        region: &Region,
+    ) -> Option<image::RgbaImage> {
+        let mut shm_capture = self.shm_capture().borrow_mut();
+        let result = shm_capture.as_mut()?.capture(
+            drawable,
+            attributes,
+            region,
+            |image| self.convert_image(attributes, image, region.width, region.height),
+        );
+        result.unwrap_or_else(|_| {
+            *shm_capture = None;
+            None
+        })
+    }
+
+    fn convert_image(
+        &self,
+        attributes: &xlib::XWindowAttributes,
+        image: *mut xlib::XImage,
+        width: u32,
+        height: u32,
+    ) -> image::RgbaImage {
+        unsafe {
+            let pixel_format = self.get_pixel_format(attributes, image);
+            let buffer = PixelBuffer {
+                data: std::slice::from_raw_parts(
+                    (*image).data as *const u8,
+                    (*image).bytes_per_line as usize * height as usize,
+                ),
+                width,
+                height,
+                bytes_per_line: (*image).bytes_per_line as usize,
+                bits_per_pixel: (*image).bits_per_pixel as u32,
+                byte_order: if (*image).byte_order == xlib::MSBFirst { ByteOrder::MsbFirst } else { ByteOrder::LsbFirst },
+            };
+            // Falls back to the (much slower) Xlib accessors for the layouts the bulk conversion doesn't handle
+            pixel_format.convert(&buffer).unwrap_or_else(|| {
+                image::RgbaImage::from_fn(width, height, |x, y| {
+                    pixel_format.to_rgba((self.xlib.XGetPixel)(image, x as i32, y as i32))
+                })
+            })
+        }
+    }
+
+    // Indexed visuals store colormap entries in the pixels, the other ones store the channels themselves
+    // https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#Visual_Types
+    fn get_pixel_format(
+        &self,
+        attributes: &xlib::XWindowAttributes,
+        image: *mut xlib::XImage,
+    ) -> PixelFormat {
+        unsafe {
+            let visual = attributes.visual;
+            let is_indexed = !visual.is_null() && matches!(
+                (*visual).class,
+                xlib::PseudoColor | xlib::StaticColor | xlib::GrayScale | xlib::StaticGray
+            );
+            if !is_indexed {
+                return PixelFormat::from_masks(
+                    (*image).red_mask,
+                    (*image).green_mask,
+                    (*image).blue_mask,
+                    attributes.depth as u32,
+                );
+            }
+            let mut colors: Vec<xlib::XColor> = (0..(*visual).map_entries.max(0) as u64)
+                .map(|pixel| xlib::XColor { pixel, ..std::mem::zeroed() })
+                .collect();
+            (self.xlib.XQueryColors)(self.display, attributes.colormap, colors.as_mut_ptr(), colors.len() as _);
+            // XColor channels are 16 bits wide
+            PixelFormat::Colormap(colors
+                .iter()
+                .map(|color| image::Rgba([(color.red >> 8) as u8, (color.green >> 8) as u8, (color.blue >> 8) as u8, u8::MAX]))
+                .collect())
+        }
+    }
+
+    // https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XGetClassHint
+    fn get_window_class(&self, window: xlib::Window) -> (Option<String>, Option<String>) {
+        unsafe {
+            let mut hint: xlib::XClassHint = std::mem::zeroed();
+
+            if (self.xlib.XGetClassHint)(self.display, window, &mut hint) == 0 {
+                return (None, None);
+            }
+
+            let read_and_free = |value: *mut i8| {
+                if value.is_null() {
+                    return None;
+                }
+                let result = CStr::from_ptr(value).to_str().ok().map(str::to_string);
+                (self.xlib.XFree)(value as _);
+                result
+            };
+            (read_and_free(hint.res_name), read_and_free(hint.res_class))
+        }
+    }
+
     fn get_window_title(
         &self,
-        window: x11::xlib::Window,
+        window: xlib::Window,
     ) -> anyhow::Result<Option<String>> {
         let wm_name = self.try_x_get_wm_name(window);
         // For older versions
@@ -102,11 +534,11 @@ impl X11DLWindowSystemAdapter {
     }
 
     // https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XGetWMName
-    fn try_x_get_wm_name(&self, window: x11::xlib::Window) -> Option<String> {
+    fn try_x_get_wm_name(&self, window: xlib::Window) -> Option<String> {
         unsafe {
-            let mut prop: x11::xlib::XTextProperty = std::mem::zeroed();
+            let mut prop: xlib::XTextProperty = std::mem::zeroed();
 
-            let ret = x11::xlib::XGetWMName(self.display, window, &mut prop);
+            let ret = (self.xlib.XGetWMName)(self.display, window, &mut prop);
             if ret == 0 {
                 return None;
             }
@@ -117,17 +549,17 @@ impl X11DLWindowSystemAdapter {
 
             let value = Some(CStr::from_ptr(prop.value as *const i8).to_str().unwrap().to_string());
 
-            x11::xlib::XFree(prop.value as _);
+            (self.xlib.XFree)(prop.value as _);
             value
         }
     }
 
     // According to https://github.com/idunham/xutils/blob/master/xwininfo.c#L487
-    fn try_x_fetch_name(&self, window: x11::xlib::Window) -> Option<String> {
+    fn try_x_fetch_name(&self, window: xlib::Window) -> Option<String> {
         unsafe {
             let mut data: *mut i8 = std::ptr::null_mut();
             
-            let ret = x11::xlib::XFetchName(self.display, window, &mut data);
+            let ret = (self.xlib.XFetchName)(self.display, window, &mut data);
             if ret == 0 {
                 return None;
             }
@@ -138,66 +570,248 @@ impl X11DLWindowSystemAdapter {
 
             let value = Some(CStr::from_ptr(data as *const i8).to_str().unwrap().to_string());
 
-            x11::xlib::XFree(data as _);
+            (self.xlib.XFree)(data as _);
             value
         }
     }
 }
 
 impl ScreenShotWindowSystemGateway for X11DLWindowSystemAdapter {
-    fn find_window(&self, searched_window_name: &String) -> anyhow::Result<Option<u64>> {
+    fn find_window(&self, selector: &WindowSelector) -> anyhow::Result<Option<u64>> {
+        let child_processes = match selector {
+            WindowSelector::Pid { pid, include_children: true } => find_descendant_processes(*pid)?,
+            _ => Vec::new(),
+        };
         let window = self.find_window_recursive_helper(
-            searched_window_name,
+            &|window| {
+                let info = self.get_window_info(window)?;
+                Ok(selector.matches(&info) || info.pid.is_some_and(|pid| child_processes.contains(&pid)))
+            },
             self.root_win,
         )?;
         Ok(window.map(|w| w as _))
     }
 
-    fn take_screen_shot(&self, window_id: u64) -> anyhow::Result<image::RgbImage> {
+    fn find_windows(&self, selector: &WindowSelector) -> anyhow::Result<Vec<WindowInfo>> {
+        let child_processes = match selector {
+            WindowSelector::Pid { pid, include_children: true } => find_descendant_processes(*pid)?,
+            _ => Vec::new(),
+        };
+        let mut result = Vec::new();
+        self.list_windows_recursive_helper(
+            &|info| selector.matches(info) || info.pid.is_some_and(|pid| child_processes.contains(&pid)),
+            self.root_win,
+            &mut result,
+        )?;
+        Ok(result)
+    }
+
+    // https://specifications.freedesktop.org/wm-spec/latest/ar01s03.html#id-1.4.10
+    // Not every window manager supports EWMH, in which case the input focus is the best guess.
+    fn get_active_window(&self) -> anyhow::Result<Option<u64>> {
+        let active_window = self.get_window_property_32(self.root_win, c"_NET_ACTIVE_WINDOW", xlib::XA_WINDOW)
+            .and_then(|windows| windows.first().copied())
+            .filter(|&window| window != 0);
+        if active_window.is_some() {
+            return Ok(active_window);
+        }
+
+        unsafe {
+            let mut focus: xlib::Window = 0;
+            let mut revert_to: i32 = 0;
+            (self.xlib.XGetInputFocus)(self.display, &mut focus, &mut revert_to);
+            // None (0) and PointerRoot (1) are not actual windows
+            if focus == 0 || focus == xlib::PointerRoot as u64 || focus == self.root_win {
+                return Ok(None);
+            }
+            Ok(Some(focus))
+        }
+    }
+
+    fn pick_window(&self) -> anyhow::Result<Option<u64>> {
+        let Some(clicked_window) = self.wait_for_window_click()? else {
+            return Ok(None);
+        };
+        Ok(Some(self.find_client_window(clicked_window)?))
+    }
+
+    fn get_root_window(&self, screen: Option<i32>) -> anyhow::Result<u64> {
+        let Some(screen) = screen else {
+            return Ok(self.root_win);
+        };
+        unsafe {
+            let screen_count = (self.xlib.XScreenCount)(self.display);
+            if screen < 0 || screen >= screen_count {
+                anyhow::bail!("Unable to find the screen {}, the display has {} screen(s)", screen, screen_count);
+            }
+            Ok((self.xlib.XRootWindow)(self.display, screen))
+        }
+    }
+
+    // Reparenting window managers put the client windows into frame windows, which are the
+    // top-level windows (direct children of the root window).
+    fn get_frame_window(&self, window_id: u64) -> anyhow::Result<u64> {
+        let mut window = window_id;
+        loop {
+            let parent = self.get_parent_window(window)?;
+            if parent == 0 || parent == self.root_win || window == self.root_win {
+                return Ok(window);
+            }
+            window = parent;
+        }
+    }
+
+    fn window_exists(&self, window_id: u64) -> anyhow::Result<bool> {
+        let window = self.find_window_recursive_helper(
+            &|window| Ok(window == window_id),
+            self.root_win,
+        )?;
+        Ok(window.is_some())
+    }
+
+    fn get_window_info(&self, window_id: u64) -> anyhow::Result<WindowInfo> {
+        let (instance, class) = self.get_window_class(window_id);
+        Ok(WindowInfo {
+            id: window_id,
+            title: self.get_window_title(window_id)?,
+            instance,
+            class,
+            pid: self.get_window_pid(window_id),
+        })
+    }
+
+    // Windows are only viewable once they and all their ancestors are mapped
+    fn is_window_mapped(&self, window_id: u64) -> anyhow::Result<bool> {
         unsafe {
-            let mut attributes: x11::xlib::XWindowAttributes = std::mem::zeroed();
-            if x11::xlib::XGetWindowAttributes(self.display, window_id, &mut attributes) == 0 {
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            if (self.xlib.XGetWindowAttributes)(self.display, window_id, &mut attributes) == 0 {
                 anyhow::bail!("Unable to get the window attributes of {:#x}", window_id);
             }
-            let width = attributes.width as u32;
-            let height = attributes.height as u32;
+            Ok(attributes.map_state == xlib::IsViewable)
+        }
+    }
 
-            let image = x11::xlib::XGetImage(
-                self.display,
-                window_id,
-                0,
-                0,
-                width as _,
-                height as _,
-                x11::xlib::XAllPlanes(),
-                x11::xlib::ZPixmap as _,
-            );
-            if image.is_null() {
-                anyhow::bail!("Unable to get the pxiel data from window {:#x}", window_id);
-            }
-            let red_mask = (*image).red_mask;
-            let green_mask = (*image).green_mask;
-            let blue_mask = (*image).blue_mask;
-            let mut imgbuf: image::RgbImage = image::ImageBuffer::new(width, height);
-            for y in 0..height {
-                for x in 0..width {
-                    let pixel = x11::xlib::XGetPixel(image, x as i32, y as i32);
-                    let r = ((pixel & red_mask) >> 16) as u8;
-                    let g = ((pixel & green_mask) >> 8) as u8;
-                    let b = (pixel & blue_mask) as u8;
-                    // https://docs.rs/image/latest/image/struct.ImageBuffer.html
-                    imgbuf.put_pixel(x, y, image::Rgb([r, g, b]));
-                }
+    fn take_screen_shot(&self, window_id: u64, region: Option<&Region>) -> anyhow::Result<image::RgbaImage> {
+        unsafe {
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            if (self.xlib.XGetWindowAttributes)(self.display, window_id, &mut attributes) == 0 {
+                anyhow::bail!("Unable to get the window attributes of {:#x}", window_id);
             }
-            Ok(imgbuf)
+            let drawable_width = attributes.width as u32;
+            let drawable_height = attributes.height as u32;
+            let region = match region {
+                Some(region) => region.clamp_to(drawable_width, drawable_height)?,
+                None => Region { x: 0, y: 0, width: drawable_width, height: drawable_height },
+            };
+            // The root window is never redirected
+            if self.offscreen && window_id != self.root_win {
+                return self.take_screen_shot_with_composite(window_id, &attributes, &region);
+            }
+            self.capture_drawable(window_id, &attributes, &region)
+        }
+    }
+
+    // https://www.x.org/releases/current/doc/damageproto/damageproto.txt
+    fn wait_for_damage(&self, window_id: u64, timeout: Duration) -> anyhow::Result<bool> {
+        let Some(damage_watch) = self.damage_watch() else {
+            anyhow::bail!("The X server does not support the Damage extension");
+        };
+        damage_watch.wait_for_damage(window_id, timeout)
+    }
+
+    // https://www.x.org/releases/current/doc/fixesproto/fixesproto.txt
+    fn get_cursor(&self, window_id: u64) -> anyhow::Result<Cursor> {
+        let xfixes = x11_dl::xfixes::Xlib::open()
+            .map_err(|e| anyhow::anyhow!("Unable to load the XFixes library: {}", e))?;
+        unsafe {
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xfixes.XFixesQueryExtension)(self.display, &mut event_base, &mut error_base) == 0 {
+                anyhow::bail!("The X server does not support the XFixes extension");
+            }
+
+            let mut window_x = 0;
+            let mut window_y = 0;
+            let mut child = 0;
+            if (self.xlib.XTranslateCoordinates)(
+                self.display, window_id, self.root_win, 0, 0, &mut window_x, &mut window_y, &mut child,
+            ) == 0 {
+                anyhow::bail!("The window {:#x} is not on the same screen as the cursor", window_id);
+            }
+
+            let cursor_image = (xfixes.XFixesGetCursorImage)(self.display);
+            if cursor_image.is_null() {
+                anyhow::bail!("Unable to get the cursor image");
+            }
+            let width = (*cursor_image).width as u32;
+            let height = (*cursor_image).height as u32;
+            // Each pixel is a premultiplied ARGB value, stored in a long
+            let pixels = std::slice::from_raw_parts((*cursor_image).pixels, (width * height) as usize);
+            let pixel_format = PixelFormat::from_masks(0xff0000, 0x00ff00, 0x0000ff, 32);
+            let cursor = Cursor {
+                x: (*cursor_image).x as i32 - (*cursor_image).xhot as i32 - window_x,
+                y: (*cursor_image).y as i32 - (*cursor_image).yhot as i32 - window_y,
+                image: image::RgbaImage::from_fn(width, height, |x, y| {
+                    pixel_format.to_rgba(pixels[(y * width + x) as usize])
+                }),
+            };
+            (self.xlib.XFree)(cursor_image as _);
+            Ok(cursor)
         }
     }
 }
 
 impl ListWindowsWindowSystemGateway for X11DLWindowSystemAdapter {
-    fn list_windows(&self) -> anyhow::Result<Vec<String>> {
+    fn list_windows(&self) -> anyhow::Result<Vec<WindowInfo>> {
         let mut result = Vec::new();
-        self.list_windows_recursive_helper(self.root_win, &mut result)?;
+        self.list_windows_recursive_helper(&|info| info.title.is_some(), self.root_win, &mut result)?;
         Ok(result)
     }
+}
+
+impl ListMonitorsWindowSystemGateway for X11DLWindowSystemAdapter {
+    // https://gitlab.freedesktop.org/xorg/proto/xorgproto/-/blob/master/randrproto.txt (RRGetMonitors)
+    // libXrandr is loaded at runtime, as it is only needed by this feature.
+    fn list_monitors(&self) -> anyhow::Result<Vec<MonitorInfo>> {
+        let xrandr = x11_dl::xrandr::Xrandr::open()
+            .map_err(|e| anyhow::anyhow!("Unable to load the XRandR library: {}", e))?;
+        unsafe {
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xrandr.XRRQueryExtension)(self.display, &mut event_base, &mut error_base) == 0 {
+                anyhow::bail!("The X server does not support the XRandR extension");
+            }
+
+            let mut nmonitors = 0;
+            let monitors = (xrandr.XRRGetMonitors)(self.display, self.root_win, xlib::True, &mut nmonitors);
+            if monitors.is_null() {
+                anyhow::bail!("Unable to get the monitors of the X server");
+            }
+
+            let result = std::slice::from_raw_parts(monitors, nmonitors as usize)
+                .iter()
+                .map(|monitor| {
+                    let name = (self.xlib.XGetAtomName)(self.display, monitor.name);
+                    let monitor_info = MonitorInfo {
+                        name: if name.is_null() {
+                            String::new()
+                        } else {
+                            CStr::from_ptr(name).to_string_lossy().to_string()
+                        },
+                        primary: monitor.primary != 0,
+                        x: monitor.x.max(0) as u32,
+                        y: monitor.y.max(0) as u32,
+                        width: monitor.width.max(0) as u32,
+                        height: monitor.height.max(0) as u32,
+                    };
+                    if !name.is_null() {
+                        (self.xlib.XFree)(name as _);
+                    }
+                    monitor_info
+                })
+                .collect();
+            (xrandr.XRRFreeMonitors)(monitors);
+            Ok(result)
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use x11_dl::xlib::{self, Xlib};
+
+static X_ERROR_OCCURRED: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" fn record_x_error(
+    _display: *mut xlib::Display,
+    _event: *mut xlib::XErrorEvent,
+) -> std::os::raw::c_int {
+    X_ERROR_OCCURRED.store(true, Ordering::SeqCst);
+    0
+}
+
+// Xlib's default error handler exits the process, whereas failing to use an extension (e.g. MIT-SHM on a remote
+// display) is expected and recoverable.
+// Returns the result of `fun`, and whether an X error has been raised meanwhile.
+pub fn trap_x_errors<T, F: FnOnce() -> T>(xlib: &Xlib, display: *mut xlib::Display, fun: F) -> (T, bool) {
+    unsafe {
+        (xlib.XSync)(display, xlib::False);
+        X_ERROR_OCCURRED.store(false, Ordering::SeqCst);
+        let previous_handler = (xlib.XSetErrorHandler)(Some(record_x_error));
+        let result = fun();
+        (xlib.XSync)(display, xlib::False);
+        (xlib.XSetErrorHandler)(previous_handler);
+        (result, X_ERROR_OCCURRED.load(Ordering::SeqCst))
+    }
+}
@@ -1,10 +1,13 @@
 use std::{env, thread};
 use std::io::BufRead;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use testcontainers::{Container, core::WaitFor, GenericImage, ImageExt, runners::SyncRunner};
 use testcontainers::core::{ExecCommand, IntoContainerPort, Mount};
 
-use borkscreen::gateways::{ListWindowsWindowSystemGateway, ScreenShotWindowSystemGateway};
+use borkscreen::region::Region;
+use borkscreen::gateways::{ListMonitorsWindowSystemGateway, ListWindowsWindowSystemGateway, ScreenShotWindowSystemGateway};
+use borkscreen::window_matcher::{MatchMode, WindowMatcher};
+use borkscreen::window_selector::WindowSelector;
 use borkscreen::window_system::x11_dl_window_system_adapter::X11DLWindowSystemAdapter;
 
 #[test]
@@ -21,7 +24,8 @@ fn test_should_list_multiple_windows() {
     let windows = sut.list_windows().expect("Failed to list windows");
 
     // Then
-    assert_eq!(windows, vec!["window1", "bbbb", "window3"]);
+    let titles: Vec<_> = windows.iter().filter_map(|window| window.title.clone()).collect();
+    assert_eq!(titles, vec!["window1", "bbbb", "window3"]);
 }
 
 #[test]
@@ -35,16 +39,36 @@ fn test_should_take_a_screenshot_among_multiple_windows() {
         .expect("Unable to create the system under test");
 
     // When
-    let window_id = sut.find_window(&"bbbb".to_string()).unwrap().unwrap();
-    let actual = sut.take_screen_shot(window_id).expect("Failed to list windows");
+    let window_id = sut.find_window(&exact("bbbb")).unwrap().unwrap();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to list windows");
 
     // Then
+    let actual = image::DynamicImage::ImageRgba8(actual).into_rgb8();
     let expected_image_path = format!("{}/tests/test_images/2.png", env::current_dir().unwrap().display());
     let expected = image::open(expected_image_path).expect("Could not find test-image").into_rgb8();
     let result = image_compare::rgb_hybrid_compare(&actual, &expected).expect("Images had different dimensions");
     assert!(result.score >= 0.9, "similarity score = {}", result.score);
 }
 
+#[test]
+fn test_should_find_every_window_sharing_a_title() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "window1", 2);
+    start_feh_process(&container, "window2", 3);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let windows = sut.find_windows(&exact("window1")).expect("Failed to find the windows");
+
+    // Then
+    assert_eq!(windows.len(), 2);
+    assert_ne!(windows[0].id, windows[1].id);
+    assert_eq!(sut.find_window(&exact("window1")).unwrap(), Some(windows[0].id));
+}
+
 #[test]
 fn test_should_return_none_if_window_cannot_be_found() {
     // Given
@@ -53,38 +77,503 @@ fn test_should_return_none_if_window_cannot_be_found() {
         .expect("Unable to create the system under test");
     
     // When
-    let window_id = sut.find_window(&"bbbb".to_string()).unwrap();
+    let window_id = sut.find_window(&exact("bbbb")).unwrap();
     
     // Then
     assert_eq!(window_id, None);
 }
 
+#[test]
+fn test_should_find_a_window_by_substring() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "editor-dirty", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let matcher = WindowMatcher::new("editor", MatchMode::Substring).unwrap();
+
+    // When
+    let window_id = sut.find_window(&WindowSelector::Title(matcher)).unwrap();
+
+    // Then
+    assert!(window_id.is_some());
+}
+
+#[test]
+fn test_should_find_a_window_by_regex() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "window22", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let matcher = WindowMatcher::new(r"^window\d{2}$", MatchMode::Regex).unwrap();
+
+    // When
+    let window_id = sut.find_window(&WindowSelector::Title(matcher)).unwrap().unwrap();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_similar_to_test_image(&actual, 2);
+}
+
+#[test]
+fn test_should_list_the_class_of_the_windows() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let windows = sut.list_windows().expect("Failed to list windows");
+
+    // Then
+    assert_eq!(windows.len(), 1);
+    assert_eq!(windows[0].title, Some("window1".to_string()));
+    assert_eq!(windows[0].instance, Some("feh".to_string()));
+    assert_eq!(windows[0].class, Some("feh".to_string()));
+}
+
+#[test]
+fn test_should_list_window_ids_that_can_be_captured() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "bash", 1);
+    start_feh_process(&container, "bash", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let windows = sut.list_windows().expect("Failed to list windows");
+    let second_window_id = windows[1].id;
+    let actual = sut.take_screen_shot(second_window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert!(sut.window_exists(second_window_id).unwrap());
+    assert_similar_to_test_image(&actual, 2);
+}
+
+#[test]
+fn test_should_find_a_window_by_pid() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "window2", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let pid = sut.list_windows().expect("Failed to list windows")[1].pid
+        .expect("feh should set the _NET_WM_PID property");
+
+    // When
+    let window_id = sut.find_window(&WindowSelector::Pid { pid, include_children: false }).unwrap().unwrap();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_similar_to_test_image(&actual, 2);
+}
+
+#[test]
+fn test_should_get_the_active_window() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "window2", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let window_id = sut.get_active_window().unwrap().expect("A window should be active");
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_similar_to_test_image(&actual, 2);
+}
+
+#[test]
+fn test_should_pick_the_clicked_client_window() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let expected_window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+    run_in_background(&container, "sleep 1 && xdotool mousemove 20 40 click 1");
+
+    // When
+    let window_id = sut.pick_window().unwrap();
+
+    // Then
+    assert_eq!(window_id, Some(expected_window_id));
+}
+
+#[test]
+fn test_should_take_a_screenshot_of_the_whole_screen() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let root_window = sut.get_root_window(None).unwrap();
+    let actual = sut.take_screen_shot(root_window, None).expect("Failed to take the screenshot");
+
+    // Then
+    let expected = image::open(test_image_path(1)).unwrap();
+    assert!(actual.width() > expected.width());
+    assert!(actual.height() > expected.height());
+}
+
+#[test]
+fn test_should_take_a_screenshot_of_a_region_of_a_window() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+    let region = Region { x: 10, y: 20, width: 30, height: 40 };
+
+    // When
+    let actual = sut.take_screen_shot(window_id, Some(&region)).expect("Failed to take the screenshot");
+
+    // Then
+    let expected = image::imageops::crop_imm(
+        &image::open(test_image_path(1)).unwrap().into_rgb8(), 10, 20, 30, 40,
+    ).to_image();
+    let actual = image::DynamicImage::ImageRgba8(actual).into_rgb8();
+    let result = image_compare::rgb_hybrid_compare(&actual, &expected).expect("Images had different dimensions");
+    assert!(result.score >= 0.9, "similarity score = {}", result.score);
+}
+
+#[test]
+fn test_should_clamp_the_region_to_the_window() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+    let expected = image::open(test_image_path(1)).unwrap();
+    let region = Region { x: 10, y: 20, width: expected.width() + 100, height: expected.height() + 100 };
+
+    // When
+    let actual = sut.take_screen_shot(window_id, Some(&region)).expect("Failed to take the screenshot");
+
+    // Then
+    assert_eq!(actual.dimensions(), (expected.width() - 10, expected.height() - 20));
+}
+
+#[test]
+fn test_should_take_a_screenshot_of_the_window_frame() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+
+    // When
+    let frame_id = sut.get_frame_window(window_id).unwrap();
+    let actual = sut.take_screen_shot(frame_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    let client = sut.take_screen_shot(window_id, None).unwrap();
+    assert_ne!(frame_id, window_id);
+    assert!(actual.height() > client.height(), "the frame should include the title bar");
+}
+
+#[test]
+fn test_should_list_the_monitors() {
+    // Given
+    let _container = run_xvfb_container();
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let root_window = sut.get_root_window(None).unwrap();
+    let screen = sut.take_screen_shot(root_window, None).expect("Failed to take the screenshot");
+
+    // When
+    let monitors = sut.list_monitors().expect("Failed to list monitors");
+
+    // Then
+    assert_eq!(monitors.len(), 1);
+    assert_eq!((monitors[0].x, monitors[0].y), (0, 0));
+    assert_eq!((monitors[0].width, monitors[0].height), screen.dimensions());
+}
+
+#[test]
+fn test_should_reject_unknown_screens() {
+    // Given
+    let _container = run_xvfb_container();
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let result = sut.get_root_window(Some(1));
+
+    // Then
+    assert_eq!(result.unwrap_err().to_string(), "Unable to find the screen 1, the display has 1 screen(s)");
+}
+
+#[test]
+fn test_should_tell_that_an_unknown_window_id_does_not_exist() {
+    // Given
+    let _container = run_xvfb_container();
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let exists = sut.window_exists(0xdeadbeef).unwrap();
+
+    // Then
+    assert!(!exists);
+}
+
+#[test]
+fn test_should_find_a_window_by_class() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let selector = WindowSelector::Class(WindowMatcher::new("feh", MatchMode::Exact).unwrap());
+
+    // When
+    let window_id = sut.find_window(&selector).unwrap().unwrap();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_similar_to_test_image(&actual, 1);
+}
+
+#[test]
+fn test_should_take_a_screenshot_on_a_16_bits_deep_screen() {
+    // Given
+    let container = run_xvfb_container_with_depth(16);
+    start_feh_process_on_display(&container, ":98", "bbbb", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let window_id = sut.find_window(&exact("bbbb")).unwrap().unwrap();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_similar_to_test_image(&actual, 2);
+}
+
+#[test]
+fn test_should_take_a_screenshot_on_a_30_bits_deep_screen() {
+    // Given
+    let container = run_xvfb_container_with_depth(30);
+    start_feh_process_on_display(&container, ":98", "bbbb", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+
+    // When
+    let window_id = sut.find_window(&exact("bbbb")).unwrap().unwrap();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_similar_to_test_image(&actual, 2);
+}
+
+#[test]
+fn test_should_take_consecutive_screenshots_of_different_sizes() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "bbbb", 2);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let window1 = sut.find_window(&exact("window1")).unwrap().unwrap();
+    let window2 = sut.find_window(&exact("bbbb")).unwrap().unwrap();
+    let region = Region { x: 0, y: 0, width: 10, height: 10 };
+
+    // When
+    let small = sut.take_screen_shot(window1, Some(&region)).expect("Failed to take the screenshot");
+    let first = sut.take_screen_shot(window1, None).expect("Failed to take the screenshot");
+    let second = sut.take_screen_shot(window2, None).expect("Failed to take the screenshot");
+
+    // Then
+    assert_eq!(small.dimensions(), (10, 10));
+    assert_similar_to_test_image(&first, 1);
+    assert_similar_to_test_image(&second, 2);
+}
+
+#[test]
+fn test_should_take_a_screenshot_of_an_obscured_window() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    start_feh_process(&container, "bbbb", 2);
+    run_in_background(&container, "xdotool search --name window1 windowmove 0 0");
+    run_in_background(&container, "xdotool search --name bbbb windowmove 0 0 windowraise");
+    thread::sleep(Duration::from_millis(200));
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test")
+        .with_offscreen_capture(true);
+
+    // When
+    let window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+    let start = Instant::now();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+    let elapsed = start.elapsed();
+
+    // Then
+    assert_similar_to_test_image(&actual, 1);
+    // The repaint is noticed, rather than waited for until the 500ms timeout
+    assert!(elapsed < Duration::from_millis(500), "elapsed = {:?}", elapsed);
+}
+
+#[test]
+fn test_should_take_a_screenshot_of_a_partially_off_screen_window() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    run_in_background(&container, "xdotool search --name window1 windowmove -- -50 -50");
+    thread::sleep(Duration::from_millis(200));
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test")
+        .with_offscreen_capture(true);
+
+    // When
+    let window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+    let start = Instant::now();
+    let actual = sut.take_screen_shot(window_id, None).expect("Failed to take the screenshot");
+    let elapsed = start.elapsed();
+
+    // Then
+    assert_similar_to_test_image(&actual, 1);
+    // The repaint is noticed, rather than waited for until the 500ms timeout
+    assert!(elapsed < Duration::from_millis(500), "elapsed = {:?}", elapsed);
+}
+
+#[test]
+fn test_should_get_the_cursor_relatively_to_the_window() {
+    // Given
+    let container = run_xvfb_container();
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let root_window = sut.get_root_window(None).unwrap();
+    run_in_background(&container, "xdotool mousemove 100 200");
+    thread::sleep(Duration::from_millis(200));
+
+    // When
+    let cursor = sut.get_cursor(root_window).expect("Failed to get the cursor");
+
+    // Then
+    let (width, height) = cursor.image.dimensions();
+    assert!(width > 0 && height > 0);
+    assert!(cursor.x <= 100 && 100 < cursor.x + width as i32, "cursor.x = {}", cursor.x);
+    assert!(cursor.y <= 200 && 200 < cursor.y + height as i32, "cursor.y = {}", cursor.y);
+}
+
+#[test]
+fn test_should_tell_whether_a_window_is_mapped() {
+    // Given
+    let container = run_xvfb_container();
+    start_feh_process(&container, "window1", 1);
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let window_id = sut.find_window(&exact("window1")).unwrap().unwrap();
+
+    // When
+    let mapped = sut.is_window_mapped(window_id).unwrap();
+    run_in_background(&container, "xdotool search --name window1 windowunmap");
+    thread::sleep(Duration::from_millis(200));
+    let unmapped = sut.is_window_mapped(window_id).unwrap();
+
+    // Then
+    assert!(mapped);
+    assert!(!unmapped);
+}
+
+#[test]
+fn test_should_wait_for_the_content_of_a_window_to_change() {
+    // Given
+    let container = run_xvfb_container();
+    let sut = X11DLWindowSystemAdapter::new()
+        .expect("Unable to create the system under test");
+    let root_window = sut.get_root_window(None).unwrap();
+    let unchanged = sut.wait_for_damage(root_window, Duration::from_millis(200)).unwrap();
+
+    // When
+    start_feh_process(&container, "window1", 1);
+    let changed = sut.wait_for_damage(root_window, Duration::from_secs(5)).unwrap();
+
+    // Then
+    assert!(!unchanged);
+    assert!(changed);
+}
+
+fn exact(title: &str) -> WindowSelector {
+    WindowSelector::Title(WindowMatcher::new(title, MatchMode::Exact).unwrap())
+}
+
+fn test_image_path(image_number: u32) -> String {
+    format!("{}/tests/test_images/{}.png", env::current_dir().unwrap().display(), image_number)
+}
+
+fn assert_similar_to_test_image(actual: &image::RgbaImage, image_number: u32) {
+    let actual = image::DynamicImage::ImageRgba8(actual.clone()).into_rgb8();
+    let expected = image::open(test_image_path(image_number)).expect("Could not find test-image").into_rgb8();
+    let result = image_compare::rgb_hybrid_compare(&actual, &expected).expect("Images had different dimensions");
+    assert!(result.score >= 0.9, "similarity score = {}", result.score);
+}
+
 fn run_xvfb_container() -> Container<GenericImage> {
     env::set_var("DISPLAY", "127.0.0.1:99.0");
     let image_mount_dir = format!("{}/tests/test_images", env::current_dir().unwrap().display());
-    let container = GenericImage::new("ultramaxu/ultramaxu-homelab-xvfb-alpine", "0.0.0")
+    GenericImage::new("ultramaxu/ultramaxu-homelab-xvfb-alpine", "0.0.0")
         .with_wait_for(WaitFor::message_on_stdout("Openbox-Debug: Moving to desktop 1"))
         .with_mapped_port(6099, 6099.tcp())
         .with_mount(Mount::bind_mount(image_mount_dir, "/images"))
         .start()
+        .expect("Unable to start xvfb container")
+}
+
+// The container's own Xvfb runs at 24 bits, so other depths are tested against a second, bare Xvfb
+// (on display :98) started in the same container.
+fn run_xvfb_container_with_depth(depth: u32) -> Container<GenericImage> {
+    let image_mount_dir = format!("{}/tests/test_images", env::current_dir().unwrap().display());
+    let container = GenericImage::new("ultramaxu/ultramaxu-homelab-xvfb-alpine", "0.0.0")
+        .with_wait_for(WaitFor::message_on_stdout("Openbox-Debug: Moving to desktop 1"))
+        .with_mapped_port(6098, 6098.tcp())
+        .with_mount(Mount::bind_mount(image_mount_dir, "/images"))
+        .start()
         .expect("Unable to start xvfb container");
+    run_in_background(&container, &format!("Xvfb :98 -screen 0 1280x1024x{} -listen tcp -ac", depth));
+    thread::sleep(Duration::from_millis(500));
+    env::set_var("DISPLAY", "127.0.0.1:98.0");
     container
 }
 
 fn start_feh_process(container: &Container<GenericImage>, title: &str, image_number: u32) {
-    let command = format!("feh --title {} /images/{}.png &", title, image_number);
+    run_in_background(container, &format!("feh --title {} /images/{}.png", title, image_number));
+    // Hey, As Long As It Works.
+    // More seriously, one has to await a bit for the feh process to actually register the window
+    // in xvfb. Even awaiting (polling) with tools like xdotool was a fruitless endeavour.
+    // Until one thinks of a better solution, this will have to do.
+    thread::sleep(Duration::from_millis(100));
+}
+
+fn start_feh_process_on_display(container: &Container<GenericImage>, display: &str, title: &str, image_number: u32) {
+    run_in_background(container, &format!("DISPLAY={} feh --title {} /images/{}.png", display, title, image_number));
+    thread::sleep(Duration::from_millis(100));
+}
+
+fn run_in_background(container: &Container<GenericImage>, command: &str) {
+    let command = format!("({}) &", command);
     let mut result = container.exec(ExecCommand::new(
         vec!["sh", "-c", command.as_str()]))
-        .expect("Unable to run the feh command");
+        .expect("Unable to run the command in the container");
     for line in result.stdout().lines() {
         println!("[STD OUT] {}", line.unwrap_or("[EMPTY LINE]".to_string()));
     }
     for line in result.stderr().lines() {
         println!("[STD ERR] {}", line.unwrap_or("[EMPTY LINE]".to_string()));
     }
-    // Hey, As Long As It Works.
-    // More seriously, one has to await a bit for the feh process to actually register the window
-    // in xvfb. Even awaiting (polling) with tools like xdotool was a fruitless endeavour.
-    // Until one thinks of a better solution, this will have to do.
-    thread::sleep(Duration::from_millis(100));
 }